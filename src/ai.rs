@@ -1,245 +1,78 @@
 extern crate shakmaty;
 
-use shakmaty::{Chess, Color, Move, Pieces, Position, Role, Setup};
+use shakmaty::{Chess, Color, Move, Position, Setup};
 
 use std::cmp::max;
 use std::cmp::min;
-
-// temporary for debugging
-static mut POSITION_COUNT: u32 = 0;
-
-// simplified evaluation arrays
-const PAWN_EVAL_WHITE: [[i32; 8]; 8] = [
-    [0, 0, 0, 0, 0, 0, 0, 0],
-    [5, 5, 5, 5, 5, 5, 5, 5],
-    [1, 1, 2, 4, 4, 2, 1, 1],
-    [0, 0, 1, 3, 3, 1, 0, 0],
-    [0, 0, 0, 2, 2, 0, 0, 0],
-    [0, -0, -1, 0, 0, -1, -0, 0],
-    [1, 1, 1, -2, -2, 1, 1, 1],
-    [0, 0, 0, 0, 0, 0, 0, 0],
-];
-
-const PAWN_EVAL_BLACK: [[i32; 8]; 8] = [
-    [0, 0, 0, 0, 0, 0, 0, 0],
-    [1, 1, 1, -2, -2, 1, 1, 1],
-    [0, -0, -1, 0, 0, -1, -0, 0],
-    [0, 0, 0, 2, 2, 0, 0, 0],
-    [0, 0, 1, 3, 3, 1, 0, 0],
-    [1, 1, 2, 4, 4, 2, 1, 1],
-    [5, 5, 5, 5, 5, 5, 5, 5],
-    [0, 0, 0, 0, 0, 0, 0, 0],
-];
-
-const KNIGHT_EVAL: [[i32; 8]; 8] = [
-    [-5, -4, -3, -3, -3, -3, -4, -5],
-    [-4, -2, 0, 0, 0, 0, -2, -4],
-    [-3, 0, 1, 1, 1, 1, 0, -3],
-    [-3, 1, 2, 2, 2, 1, 0, -3],
-    [-3, 1, 2, 2, 2, 1, 0, -3],
-    [-3, 0, 1, 1, 1, 1, 0, -3],
-    [-4, -2, 0, 0, 0, 0, -2, -4],
-    [-5, -4, -3, -3, -3, -3, -4, -5],
-];
-
-const BISHOP_EVAL_WHITE: [[i32; 8]; 8] = [
-    [-4, -2, -2, -2, -2, -2, -2, -4],
-    [-2, 0, 0, 0, 0, 0, 0, -2],
-    [-2, 0, 1, 2, 2, 2, 0, -2],
-    [-2, 1, 1, 2, 2, 2, 1, -2],
-    [-2, 0, 2, 2, 2, 2, 0, -2],
-    [-2, 2, 2, 2, 2, 2, 2, -2],
-    [-2, 1, 0, 0, 0, 0, 1, -2],
-    [-4, -2, -2, -2, -2, -2, -2, -4],
-];
-
-const BISHOP_EVAL_BLACK: [[i32; 8]; 8] = [
-    [-4, -2, -2, -2, -2, -2, -2, -4],
-    [-2, 1, 0, 0, 0, 0, 1, -2],
-    [-2, 2, 2, 2, 2, 2, 2, -2],
-    [-2, 0, 2, 2, 2, 2, 0, -2],
-    [-2, 1, 1, 2, 2, 2, 1, -2],
-    [-2, 0, 1, 2, 2, 2, 0, -2],
-    [-2, 0, 0, 0, 0, 0, 0, -2],
-    [-4, -2, -2, -2, -2, -2, -2, -4],
-];
-
-const ROOK_EVAL_WHITE: [[i32; 8]; 8] = [
-    [0, 0, 0, 0, 0, 0, 0, 0],
-    [1, 2, 2, 2, 2, 2, 2, 1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [0, 0, 0, 1, 1, 0, 0, 0],
-];
-
-const ROOK_EVAL_BLACK: [[i32; 8]; 8] = [
-    [0, 0, 0, 1, 1, 0, 0, 0],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [-1, 0, 0, 0, 0, 0, 0, -1],
-    [1, 2, 2, 2, 2, 2, 2, 1],
-    [0, 0, 0, 0, 0, 0, 0, 0],
-];
-
-const EVAL_QUEEN: [[i32; 8]; 8] = [
-    [-4, -2, -2, -1, -1, -2, -2, -4],
-    [-2, 0, 0, 0, 0, 0, 0, -2],
-    [-2, 0, 1, 1, 1, 1, 0, -2],
-    [-1, 0, 1, 1, 1, 1, 0, -1],
-    [0, 0, 1, 1, 1, 1, 0, -1],
-    [-2, 1, 1, 1, 1, 1, 0, -2],
-    [-2, 0, 1, 0, 0, 0, 0, -2],
-    [-4, -2, -2, -1, -1, -2, -2, -4],
-];
-
-const KING_EVAL_WHITE: [[i32; 8]; 8] = [
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-    [-2, -3, -3, -4, -4, -3, -3, -2],
-    [-1, -2, -2, -2, -2, -2, -2, -1],
-    [2, 2, 0, 0, 0, 0, 2, 2],
-    [2, 3, 1, 0, 0, 1, 3, 2],
-];
-
-const KING_EVAL_BLACK: [[i32; 8]; 8] = [
-    [2, 3, 1, 0, 0, 1, 3, 2],
-    [2, 2, 0, 0, 0, 0, 2, 2],
-    [-1, -2, -2, -2, -2, -2, -2, -1],
-    [-2, -3, -3, -4, -4, -3, -3, -2],
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-    [-3, -4, -4, -5, -5, -4, -4, -3],
-];
-
-pub fn get_values(pieces: &Pieces) -> i32 {
-    let mut total = 0;
-
-    let get_value_from_eval = |pieces: &Pieces, eval: &[[i32; 8]; 8], index: usize| {
-        eval[pieces
-            .to_owned()
-            .nth(index)
-            .unwrap()
-            .0
-            .rank()
-            .flip_vertical()
-            .char() as usize
-            - '1' as usize]
-            [pieces.to_owned().nth(index).unwrap().0.file().char() as usize - 'a' as usize]
-    };
-
-    for i in 0..pieces.len() {
-        // kings
-        let _ = pieces
-            .to_owned()
-            .nth(i)
-            .filter(|piece| piece.1.role == Role::King)
-            .map(|piece| {
-                if piece.1.color == Color::White {
-                    total += 900 + get_value_from_eval(&pieces, &KING_EVAL_WHITE, i)
-                } else {
-                    total -= 900 + get_value_from_eval(&pieces, &KING_EVAL_BLACK, i)
-                }
-            });
-
-        // queens
-        let _ = pieces
-            .to_owned()
-            .nth(i)
-            .filter(|piece| piece.1.role == Role::Queen)
-            .map(|piece| {
-                if piece.1.color == Color::White {
-                    total += 90 + get_value_from_eval(&pieces, &EVAL_QUEEN, i)
-                } else {
-                    total -= 90 + get_value_from_eval(&pieces, &EVAL_QUEEN, i)
-                }
-            });
-
-        // rooks
-        let _ = pieces
-            .to_owned()
-            .nth(i)
-            .filter(|piece| piece.1.role == Role::Rook)
-            .map(|piece| {
-                if piece.1.color == Color::White {
-                    total += 50 + get_value_from_eval(&pieces, &ROOK_EVAL_WHITE, i)
-                } else {
-                    total -= 50 + get_value_from_eval(&pieces, &ROOK_EVAL_BLACK, i)
-                }
-            });
-
-        // bishops
-        let _ = pieces
-            .to_owned()
-            .nth(i)
-            .filter(|piece| piece.1.role == Role::Bishop)
-            .map(|piece| {
-                if piece.1.color == Color::White {
-                    total += 30 + get_value_from_eval(&pieces, &BISHOP_EVAL_WHITE, i)
-                } else {
-                    total -= 30 + get_value_from_eval(&pieces, &BISHOP_EVAL_BLACK, i)
-                }
-            });
-
-        // knights
-        let _ = pieces
-            .to_owned()
-            .nth(i)
-            .filter(|piece| piece.1.role == Role::Knight)
-            .map(|piece| {
-                if piece.1.color == Color::White {
-                    total += 30 + get_value_from_eval(&pieces, &KNIGHT_EVAL, i)
-                } else {
-                    total -= 30 + get_value_from_eval(&pieces, &KNIGHT_EVAL, i)
-                }
-            });
-
-        // pawns
-        let _ = pieces
-            .to_owned()
-            .nth(i)
-            .filter(|piece| piece.1.role == Role::Pawn)
-            .map(|piece| {
-                if piece.1.color == Color::White {
-                    total += 10 + get_value_from_eval(&pieces, &PAWN_EVAL_WHITE, i)
-                } else {
-                    total -= 10 + get_value_from_eval(&pieces, &PAWN_EVAL_BLACK, i)
-                }
-            });
-    }
-
-    total
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::eval::{self, PawnTable};
+use crate::params::EvalParams;
+use crate::syzygy::{Tablebase, Wdl};
+
+// a tablebase win, less than the 9999 of a mate
+const TB_WIN: i32 = 5000;
+
+// What minimax takes down the tree besides the position: what it evaluates
+// with, and what it counts.
+pub struct Context<'a> {
+    pub params: &'a EvalParams,
+    // only ever filled with `params`
+    pub pawns: &'a mut PawnTable,
+    pub tablebase: Option<&'a Tablebase>,
+    pub nodes: u64,
 }
 
 // special thanks to https://www.freecodecamp.org/news/simple-chess-ai-step-by-step-1d55a9266977/
 //
 // Recursive function to decide the best move based on the future
 // (This does not gives us the *really* best move, it just sieves out the dumb moves
-pub fn minimax(depth: u32, game: Chess, mut alpha: i32, mut beta: i32) -> i32 {
-    unsafe {
-        POSITION_COUNT += 1;
+pub fn minimax(
+    depth: u32,
+    game: Chess,
+    mut alpha: i32,
+    mut beta: i32,
+    context: &mut Context,
+) -> i32 {
+    context.nodes += 1;
+
+    // the tablebases know better than the evaluation
+    if let Some(wdl) = context
+        .tablebase
+        .and_then(|tablebase| tablebase.probe_wdl(&game))
+    {
+        let score = match wdl {
+            // sooner is better, there's more depth left
+            Wdl::Win => TB_WIN + depth as i32,
+            Wdl::Loss => -TB_WIN - depth as i32,
+            _ => 0,
+        };
+        return if game.turn() == Color::Black {
+            score
+        } else {
+            -score
+        };
     }
 
     if depth == 0 {
-        return -get_values(&game.board().pieces());
+        return -eval::evaluate(game.board(), context.params, context.pawns);
     }
 
     let new_game_moves = game.legals();
 
+    // no moves and not in check is stalemate, a draw and not a mate
+    if new_game_moves.is_empty() && !game.is_check() {
+        return 0;
+    }
+
     if game.turn() == shakmaty::Color::Black {
         let mut best_move = -9999;
         for i in 0..new_game_moves.len() {
             let temp_board = game.to_owned().play(&new_game_moves[i]);
             best_move = max(
                 best_move,
-                minimax(depth - 1, temp_board.unwrap(), alpha, beta),
+                minimax(depth - 1, temp_board.unwrap(), alpha, beta, context),
             );
 
             alpha = max(alpha, best_move);
@@ -254,7 +87,7 @@ pub fn minimax(depth: u32, game: Chess, mut alpha: i32, mut beta: i32) -> i32 {
             let temp_board = game.to_owned().play(&new_game_moves[i]);
             best_move = min(
                 best_move,
-                minimax(depth - 1, temp_board.unwrap(), alpha, beta),
+                minimax(depth - 1, temp_board.unwrap(), alpha, beta, context),
             );
 
             beta = min(beta, best_move);
@@ -266,35 +99,219 @@ pub fn minimax(depth: u32, game: Chess, mut alpha: i32, mut beta: i32) -> i32 {
     }
 }
 
-pub fn minimax_root(depth: u32, game: &mut Chess) -> Move {
-    unsafe {
-        POSITION_COUNT += 1;
+#[derive(Clone, Debug)]
+pub struct SearchResult {
+    pub best_move: Move,
+    // from the point of view of the side to move, a pawn is 10
+    pub score: i32,
+    pub depth: u32,
+    // positions evaluated
+    pub nodes: u64,
+    // positions found in the tablebases
+    pub tb_hits: u64,
+    // every root move searched with its score, the same way as `score`
+    pub moves: Vec<(Move, i32)>,
+}
+
+// None when there's no legal move to search, with the built-in evaluation
+// parameters
+pub fn search(depth: u32, game: &Chess) -> Option<SearchResult> {
+    search_with(depth, game, None, &Rc::new(EvalParams::default()))
+}
+
+fn search_with(
+    depth: u32,
+    game: &Chess,
+    tablebase: Option<&Rc<Tablebase>>,
+    params: &Rc<EvalParams>,
+) -> Option<SearchResult> {
+    let mut search = RootSearch::new(depth, game).with_params(Rc::clone(params));
+    if let Some(tablebase) = tablebase {
+        search = search.with_tablebase(Rc::clone(tablebase));
     }
-    let new_game_moves = game.legals();
-    let mut best_value = -9999;
+    while !search.step() {}
+    search.result()
+}
 
-    // arbitrary value to avoid undefined behaviour
-    let mut best_move_found: Move = new_game_moves[0].clone();
+// A search that can be done one root move at a time, for callers with a
+// frame to draw in between (the browser, where nothing may block for long).
+pub struct RootSearch {
+    game: Chess,
+    depth: u32,
+    moves: Vec<Move>,
+    // the next root move to search
+    next: usize,
+    // minimax scores from black's point of view, so white wants the lowest one
+    maximizing: bool,
+    best_value: i32,
+    best_move: Option<Move>,
+    // of the root moves searched so far, in the same order
+    scores: Vec<i32>,
+    nodes: u64,
+    tablebase: Option<Rc<Tablebase>>,
+    // the tablebase's hits before this search
+    tb_hits: u64,
+    params: Rc<EvalParams>,
+    pawns: PawnTable,
+}
 
-    for i in 0..new_game_moves.len() {
-        let new_game_move = &new_game_moves[i];
+impl RootSearch {
+    pub fn new(depth: u32, game: &Chess) -> RootSearch {
+        let moves: Vec<Move> = game.legals().into_iter().collect();
+        let maximizing = game.turn() == Color::Black;
+
+        RootSearch {
+            game: game.clone(),
+            // the root moves are searched one ply less deep
+            depth: depth.max(1),
+            best_move: None,
+            scores: Vec::new(),
+            moves,
+            next: 0,
+            maximizing,
+            best_value: if maximizing { -9999 } else { 9999 },
+            nodes: 1,
+            tablebase: None,
+            tb_hits: 0,
+            params: Rc::new(EvalParams::default()),
+            pawns: PawnTable::new(),
+        }
+    }
 
-        let temp_board = game.to_owned().play(&new_game_move);
+    // evaluates with `params` instead of the built-in ones
+    pub fn with_params(mut self, params: Rc<EvalParams>) -> RootSearch {
+        self.params = params;
+        self.pawns = PawnTable::new();
+        self
+    }
 
-        let curr_value = minimax(depth - 1, temp_board.unwrap(), -10000, 10000);
+    // Probes the tablebase in the search, and in a position it has, only
+    // searches the root moves that keep its result.
+    pub fn with_tablebase(mut self, tablebase: Rc<Tablebase>) -> RootSearch {
+        self.tb_hits = tablebase.hits();
+        if let Some(moves) = tablebase.best_moves(&self.game) {
+            self.moves = moves;
+        }
+        self.tablebase = Some(tablebase);
+        self
+    }
 
-        if curr_value >= best_value {
-            best_value = curr_value;
-            best_move_found = new_game_move.clone();
+    // searches the next root move, true once they're all done
+    pub fn step(&mut self) -> bool {
+        if let Some(m) = self.moves.get(self.next) {
+            let temp_board = self.game.to_owned().play(m);
+            let mut context = Context {
+                params: &self.params,
+                pawns: &mut self.pawns,
+                tablebase: self.tablebase.as_deref(),
+                nodes: self.nodes,
+            };
+            let curr_value = minimax(
+                self.depth - 1,
+                temp_board.unwrap(),
+                -10000,
+                10000,
+                &mut context,
+            );
+            self.nodes = context.nodes;
+
+            if (self.maximizing && curr_value >= self.best_value)
+                || (!self.maximizing && curr_value <= self.best_value)
+            {
+                self.best_value = curr_value;
+                self.best_move = Some(m.clone());
+            }
+            self.scores.push(curr_value);
+
+            self.next += 1;
         }
+
+        self.next >= self.moves.len()
     }
 
-    unsafe {
-        println!("positions evaluated: {}", POSITION_COUNT);
-        POSITION_COUNT = 0;
+    // the best move of the root moves searched so far, None before the
+    // first one or if there are none
+    pub fn result(&self) -> Option<SearchResult> {
+        let for_side = |value: i32| if self.maximizing { value } else { -value };
+
+        Some(SearchResult {
+            best_move: self.best_move.clone()?,
+            score: for_side(self.best_value),
+            depth: self.depth,
+            nodes: self.nodes,
+            tb_hits: self
+                .tablebase
+                .as_ref()
+                .map_or(0, |tablebase| tablebase.hits() - self.tb_hits),
+            // every root move gets the full window, so these are exact
+            moves: self
+                .moves
+                .iter()
+                .cloned()
+                .zip(self.scores.iter().map(|value| for_side(*value)))
+                .collect(),
+        })
+    }
+}
+
+pub fn minimax_root(depth: u32, game: &mut Chess) -> Option<Move> {
+    search(depth, game).map(|result| result.best_move)
+}
+
+// Searches `depth` plies deep, or with a `movetime` keeps searching one ply
+// deeper (up to `depth`) for as long as the next iteration looks like it
+// still fits in the time. With a `tablebase`, positions it has are scored
+// from it, the others with `params`.
+pub fn search_timed(
+    game: &Chess,
+    depth: u32,
+    movetime: Option<Duration>,
+    tablebase: Option<&Rc<Tablebase>>,
+    params: &Rc<EvalParams>,
+) -> Option<SearchResult> {
+    search_limited(game, depth, movetime, None, tablebase, params)
+}
+
+// `search_timed` with a budget of nodes as well, the same way: no deeper
+// iteration is started that looks like it would go over it.
+pub fn search_limited(
+    game: &Chess,
+    depth: u32,
+    movetime: Option<Duration>,
+    max_nodes: Option<u64>,
+    tablebase: Option<&Rc<Tablebase>>,
+    params: &Rc<EvalParams>,
+) -> Option<SearchResult> {
+    if movetime.is_none() && max_nodes.is_none() {
+        return search_with(depth, game, tablebase, params);
+    }
+
+    let start = Instant::now();
+    let mut best = search_with(1, game, tablebase, params)?;
+    let mut nodes = best.nodes;
+    let mut tb_hits = best.tb_hits;
+    let mut last_iteration = (start.elapsed(), best.nodes);
+
+    for d in 2..=depth {
+        // every ply costs several times the previous one
+        let out_of_time =
+            movetime.is_some_and(|movetime| start.elapsed() + last_iteration.0 * 6 > movetime);
+        let out_of_nodes =
+            max_nodes.is_some_and(|max_nodes| nodes + last_iteration.1 * 6 > max_nodes);
+        if out_of_time || out_of_nodes {
+            break;
+        }
+
+        let iteration_start = Instant::now();
+        best = search_with(d, game, tablebase, params)?;
+        nodes += best.nodes;
+        tb_hits += best.tb_hits;
+        last_iteration = (iteration_start.elapsed(), best.nodes);
     }
 
-    best_move_found
+    best.nodes = nodes;
+    best.tb_hits = tb_hits;
+    Some(best)
 }
 
 #[cfg(test)]
@@ -304,6 +321,81 @@ mod tests {
     #[test]
     fn test_get_values() {
         let game = Chess::default();
-        assert_eq!(get_values(&game.board().pieces()), 0);
+        let params = EvalParams::default();
+        assert_eq!(
+            eval::evaluate(game.board(), &params, &mut PawnTable::new()),
+            0
+        );
+
+        // the pieces at 10/30/30/50/90/900, and the knights at home -4 each,
+        // both in centipawns
+        let explanation = eval::explain(game.board(), &params);
+        for terms in &[explanation.white, explanation.black] {
+            assert_eq!(terms.material.mg, 12900);
+            assert_eq!(terms.squares[1].mg, -80);
+        }
+    }
+
+    #[test]
+    fn test_king_centralises_in_endgames() {
+        let fen = |fen: &str| crate::notation::position_from_fen(fen).unwrap();
+        let params = EvalParams::default();
+        let evaluate = |game: &Chess| eval::explain(game.board(), &params).score();
+
+        // with all the pieces on the king is better off at home, with only
+        // pawns it should be in the middle
+        let home = fen("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w - - 0 1");
+        let out = fen("rnbqkbnr/pppppppp/8/8/4K3/8/PPPPPPPP/RNBQ1BNR w - - 0 1");
+        assert!(evaluate(&home) > evaluate(&out));
+        let home = fen("4k3/8/8/3p4/3P4/8/8/4K3 w - - 0 1");
+        let out = fen("4k3/8/8/3p4/3P4/4K3/8/8 w - - 0 1");
+        assert!(evaluate(&home) < evaluate(&out));
+
+        // and walks there instead of back to the corner
+        let result = search(3, &out).unwrap();
+        assert_eq!(result.best_move.role(), shakmaty::Role::King);
+        assert!(result.best_move.to().rank() >= shakmaty::Rank::Fourth);
+    }
+
+    #[test]
+    fn test_stalemate_is_a_draw() {
+        let fen = |fen: &str| crate::notation::position_from_fen(fen).unwrap();
+        let params = EvalParams::default();
+        let mut pawns = PawnTable::new();
+        let mut context = Context {
+            params: &params,
+            pawns: &mut pawns,
+            tablebase: None,
+            nodes: 0,
+        };
+        let stalemate = fen("8/8/8/8/8/8/4Q3/K1k5 b - - 0 1");
+        assert_eq!(minimax(2, stalemate, -10000, 10000, &mut context), 0);
+        let mate = fen("k7/1Q6/1K6/8/8/8/8/8 b - - 0 1");
+        assert_eq!(minimax(2, mate, -10000, 10000, &mut context), -9999);
+
+        // so a queen up, it isn't the way to win
+        let game = fen("8/8/8/8/8/8/Q7/K1k5 w - - 0 1");
+        let result = search(2, &game).unwrap();
+        assert!(!game.play(&result.best_move).unwrap().is_stalemate());
+    }
+
+    #[test]
+    fn test_searches_keep_their_own_params() {
+        // the knight is worth nothing to one of them, so it gives it away
+        let game = crate::notation::position_from_fen("4k3/8/3p4/8/3N4/8/8/4K3 w - - 0 1").unwrap();
+        let mut cheap = EvalParams::default();
+        cheap.values[1] = 0;
+        let (cheap, default) = (Rc::new(cheap), Rc::new(EvalParams::default()));
+
+        let alone = |params: &Rc<EvalParams>| search_with(3, &game, None, params).unwrap();
+        let (cheap_alone, default_alone) = (alone(&cheap), alone(&default));
+        assert_ne!(cheap_alone.moves, default_alone.moves);
+
+        // taking turns on one thread, the way two engines in a match do
+        let mut first = RootSearch::new(3, &game).with_params(Rc::clone(&cheap));
+        let mut second = RootSearch::new(3, &game).with_params(Rc::clone(&default));
+        while !(first.step() & second.step()) {}
+        assert_eq!(first.result().unwrap().moves, cheap_alone.moves);
+        assert_eq!(second.result().unwrap().moves, default_alone.moves);
     }
 }
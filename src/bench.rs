@@ -0,0 +1,110 @@
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::ai;
+use crate::notation;
+use crate::params::EvalParams;
+
+// A second or two in a release build.
+pub const DEFAULT_DEPTH: u32 = 4;
+
+// Openings, middlegames and endgames, a bit of everything the search sees.
+// Most come from stockfish's bench.
+const POSITIONS: [&str; 40] = [
+    "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+    "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 10",
+    "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 11",
+    "4rrk1/pp1n3p/3q2pQ/2p1pb2/2PP4/2P3N1/P2B2PP/4RRK1 b - - 7 19",
+    "rq3rk1/ppp2ppp/1bnpb3/3N2B1/3NP3/7P/PPPQ1PP1/2KR3R w - - 7 14",
+    "r1bq1r1k/1pp1n1pp/1p1p4/4p2Q/4Pp2/1BNP4/PPP2PPP/3R1RK1 w - - 2 14",
+    "r3r1k1/2p2ppp/p1p1bn2/8/1q2P3/2NPQN2/PPP3PP/R4RK1 b - - 2 15",
+    "r1bbk1nr/pp3p1p/2n5/1N4p1/2Np1B2/8/PPP2PPP/2KR1B1R w kq - 0 13",
+    "r1bq1rk1/ppp1nppp/4n3/3p3Q/3P4/1BP1B3/PP1N2PP/R4RK1 w - - 1 16",
+    "4r1k1/r1q2ppp/ppp2n2/4P3/5Rb1/1N1BQ3/PPP3PP/R5K1 w - - 1 17",
+    "2rqkb1r/ppp2p2/2npb1p1/1N1Nn2p/2P1PP2/8/PP2B1PP/R1BQK2R b KQ - 0 11",
+    "r1bq1r1k/b1p1npp1/p2p3p/1p6/3PP3/1B2NN2/PP3PPP/R2Q1RK1 w - - 1 16",
+    "3r1rk1/p5pp/bpp1pp2/8/q1PP1P2/b3P3/P2NQRPP/1R2B1K1 b - - 6 22",
+    "r1q2rk1/2p1bppp/2Pp4/p6b/Q1PNp3/4B3/PP1R1PPP/2K4R w - - 2 18",
+    "4k2r/1pb2ppp/1p2p3/1R1p4/3P4/2r1PN2/P4PPP/1R4K1 b - - 3 22",
+    "3q2k1/pb3p1p/4pbp1/2r5/PpN2N2/1P2P2P/5PP1/Q2R2K1 b - - 4 26",
+    "6k1/6p1/6Pp/ppp5/3pn2P/1P3K2/1PP2P2/8 b - - 3 54",
+    "r2r1n2/pp2bk2/2p1p2p/3q4/3PN1QP/2P3R1/P4PP1/5RK1 w - - 0 1",
+    "6k1/3b3r/1p1p4/p1n2p2/1PPNpP1q/P3Q1p1/1R1RB1P1/5K2 b - - 0 1",
+    "rnbqkb1r/pppp1ppp/5n2/4p3/2B1P3/8/PPPP1PPP/RNBQK1NR w KQkq - 2 3",
+    "r1bqkbnr/pp1ppppp/2n5/2p5/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+    "rnbqkb1r/ppp1pppp/5n2/3p4/3P1B2/5N2/PPP1PPPP/RN1QKB1R b KQkq - 3 3",
+    "r1bqk2r/pppp1ppp/2n2n2/2b1p3/2B1P3/2N2N2/PPPP1PPP/R1BQK2R w KQkq - 6 5",
+    "rnbq1rk1/ppp1ppbp/3p1np1/8/2PPP3/2N2N2/PP3PPP/R1BQKB1R w KQ - 1 6",
+    "r2q1rk1/pp2bppp/2n1pn2/3p4/2PP4/2N1PN2/PP2BPPP/R2Q1RK1 w - - 0 10",
+    "2r2rk1/pp1bqpp1/2n1pn1p/3p4/3P4/P1NBPN2/1PQ2PPP/2R2RK1 w - - 4 15",
+    "r4rk1/1b2bppp/p1nqpn2/1p6/3P4/P1NB1N2/1P2QPPP/R1B2RK1 w - - 3 14",
+    "2kr3r/ppp2ppp/2n5/2b1p3/4P1b1/2P2N2/PP1N1PPP/R1B1KB1R w KQ - 4 10",
+    "8/8/8/8/5kp1/P7/8/1K1N4 w - - 0 80",
+    "8/8/8/5N2/8/p7/8/2NK3k w - - 0 82",
+    "8/3k4/8/8/8/4B3/4KB2/2B5 w - - 0 85",
+    "8/8/1P6/5pr1/8/4R3/7k/2K5 w - - 0 88",
+    "8/2p4P/8/kr6/6R1/8/8/1K6 w - - 0 94",
+    "8/8/3P3k/8/1p6/8/1P6/1K3n2 b - - 0 90",
+    "8/R7/2q5/8/6k1/8/1P5p/K6R w - - 0 124",
+    "8/8/8/8/8/5k2/6p1/6K1 w - - 0 1",
+    "7k/8/6KP/8/8/3B4/8/8 b - - 0 1",
+    "8/5pk1/6p1/8/3R4/6PP/r4PK1/8 w - - 0 40",
+    "8/pp3k2/2p5/3p1p2/3P1Pp1/2P3P1/PP4K1/8 w - - 0 35",
+    "4k3/8/8/8/8/8/4P3/4K3 w - - 0 1",
+];
+
+// What a benchmark came to.
+#[derive(Clone, Copy, Debug)]
+pub struct Bench {
+    pub positions: usize,
+    // positions searched, the same on every machine for the same depth
+    pub nodes: u64,
+    pub time: Duration,
+}
+
+impl Bench {
+    pub fn nps(&self) -> u64 {
+        let millis = self.time.as_millis().max(1) as u64;
+        self.nodes * 1000 / millis
+    }
+}
+
+// Searches every one of the positions `depth` plies deep with the built-in
+// evaluation parameters. The node count works as a signature: anything that
+// changes it changes how the engine plays.
+pub fn bench(depth: u32) -> Bench {
+    let params = Rc::new(EvalParams::default());
+
+    let start = Instant::now();
+    let mut nodes = 0;
+    for fen in POSITIONS.iter() {
+        let game = notation::position_from_fen(fen).expect("the bench positions are valid");
+        if let Some(result) = ai::search_timed(&game, depth, None, None, &params) {
+            nodes += result.nodes;
+        }
+    }
+    let time = start.elapsed();
+
+    Bench {
+        positions: POSITIONS.len(),
+        nodes,
+        time,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_bench() {
+        let first = bench(2);
+        assert_eq!(first.positions, 40);
+        assert!(first.nodes > 40);
+        for fen in POSITIONS.iter() {
+            let game = notation::position_from_fen(fen).unwrap();
+            assert!(ai::search(1, &game).is_some(), "{}", fen);
+        }
+        assert_eq!(bench(2).nodes, first.nodes);
+    }
+}
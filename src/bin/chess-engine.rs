@@ -0,0 +1,29 @@
+extern crate chess;
+
+use std::env;
+use std::io;
+use std::process;
+
+use chess::error::Error;
+
+// The engine without the board: talks UCI on stdin/stdout, so it can be used
+// from any chess GUI or over SSH. `chess-engine bench [depth]` runs the
+// benchmark and exits.
+fn main() {
+    let args: Vec<String> = env::args().skip(1).collect();
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    let result = if args.first().map(String::as_str) == Some("bench") {
+        let command = args.join(" ") + "\n";
+        chess::uci::run(command.as_bytes(), stdout.lock())
+    } else {
+        chess::uci::run(stdin.lock(), stdout.lock())
+    };
+
+    if let Err(err) = result {
+        let err = Error::io("chess-engine", err);
+        eprintln!("{}", err);
+        process::exit(err.exit_code());
+    }
+}
@@ -0,0 +1,166 @@
+extern crate chess;
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use chess::ai;
+use chess::epd;
+use chess::error::Error;
+use chess::notation;
+use chess::params::EvalParams;
+use chess::syzygy::Tablebase;
+
+const HELP: &str = "\
+chess-epd - runs the engine through a suite of test positions.
+
+USAGE:
+    chess-epd <file.epd> [OPTIONS]
+
+Every line of the file is a position with the best moves (bm) or the moves
+to avoid (am) in SAN, and usually an id, like
+    2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";
+
+OPTIONS:
+    --depth <plies>       search this deep (default: 3, or as deep as fits in
+                          --movetime)
+    --movetime <ms>       search every position for about this long
+    --min <n>             exit with 1 when fewer positions are solved
+                          (default: all of them)
+    --syzygy <dir>        score endgames from these Syzygy tablebases
+    --eval-params <file>  evaluate with these parameters
+    -h, --help            print this and exit
+";
+
+// the deepest a timed search goes
+const MAX_DEPTH: u32 = 64;
+
+fn main() {
+    match run() {
+        Ok(true) => {}
+        Ok(false) => process::exit(1),
+        Err(err) => {
+            eprintln!("chess-epd: {}", err);
+            process::exit(err.exit_code());
+        }
+    }
+}
+
+// whether enough positions were solved
+fn run() -> Result<bool, Error> {
+    let mut file = None;
+    let mut depth = None;
+    let mut movetime = None;
+    let mut min = None;
+    let mut tablebase = None;
+    let mut params = Rc::new(EvalParams::default());
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| Error::Config(format!("{} needs a value", arg)))
+        };
+        let invalid = |value: &str| Error::Config(format!("invalid value for {}: {}", arg, value));
+
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{}", HELP);
+                return Ok(true);
+            }
+            "--depth" | "--movetime" | "--min" => {
+                let value = value()?;
+                let number: u64 = value.parse().map_err(|_| invalid(&value))?;
+                match arg.as_str() {
+                    "--depth" if number > 0 => depth = Some(number as u32),
+                    "--movetime" => movetime = Some(Duration::from_millis(number)),
+                    "--min" => min = Some(number as usize),
+                    _ => return Err(invalid(&value)),
+                }
+            }
+            "--syzygy" => {
+                let path = PathBuf::from(value()?);
+                tablebase = Some(Rc::new(Tablebase::open(&path)?));
+            }
+            "--eval-params" => {
+                let path = PathBuf::from(value()?);
+                params = Rc::new(EvalParams::load(&path)?);
+            }
+            _ if arg.starts_with("--") || file.is_some() => {
+                return Err(Error::Config(format!("unexpected argument: {}", arg)))
+            }
+            _ => file = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let file = file.ok_or_else(|| Error::Config("which suite? see --help".to_owned()))?;
+    let entries = epd::load(&file)?;
+    let depth = depth.unwrap_or(if movetime.is_some() { MAX_DEPTH } else { 3 });
+
+    let mut solved = 0;
+    let mut nodes = 0;
+    let start = Instant::now();
+
+    for (i, entry) in entries.iter().enumerate() {
+        let id = entry.id.clone().unwrap_or_else(|| format!("#{}", i + 1));
+        let san = |moves: &[_]| -> String {
+            let moves: Vec<String> = moves
+                .iter()
+                .map(|m| notation::san(&entry.game, m))
+                .collect();
+            moves.join(" ")
+        };
+        let expected = if entry.best.is_empty() {
+            format!("am {}", san(&entry.avoid))
+        } else {
+            format!("bm {}", san(&entry.best))
+        };
+
+        let searched = Instant::now();
+        let result = ai::search_timed(&entry.game, depth, movetime, tablebase.as_ref(), &params);
+        if let Some(err) = tablebase
+            .as_ref()
+            .and_then(|tablebase| tablebase.take_error())
+        {
+            return Err(err);
+        }
+        let result = match result {
+            Some(result) => result,
+            None => {
+                println!("{:<12} no legal moves      {}", id, expected);
+                continue;
+            }
+        };
+        let time = searched.elapsed();
+        nodes += result.nodes;
+
+        let ok = entry.solved_by(&result.best_move);
+        if ok {
+            solved += 1;
+        }
+        println!(
+            "{:<12} {:<6} {:<7} {:<16} depth {:<2} {:>10} nodes {:>7.2}s",
+            id,
+            if ok { "solved" } else { "failed" },
+            notation::san(&entry.game, &result.best_move),
+            expected,
+            result.depth,
+            result.nodes,
+            time.as_secs_f64()
+        );
+    }
+
+    let time = start.elapsed();
+    println!(
+        "\nsolved {}/{}, {} nodes in {:.2}s ({:.0} nodes/s)",
+        solved,
+        entries.len(),
+        nodes,
+        time.as_secs_f64(),
+        nodes as f64 / time.as_secs_f64().max(0.001)
+    );
+
+    Ok(solved >= min.unwrap_or(entries.len()))
+}
@@ -0,0 +1,220 @@
+extern crate chess;
+extern crate shakmaty;
+
+use shakmaty::{Chess, Color};
+
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::path::PathBuf;
+use std::process;
+
+use chess::config::Config;
+use chess::error::Error;
+use chess::player::PlayerSpec;
+use chess::tournament::{self, Adjudication, Contestant, Sprt, Tally, Verdict};
+
+const HELP: &str = "\
+chess-match - plays two engines against each other.
+
+USAGE:
+    chess-match [OPTIONS] --engine <player> [OPTIONS] --engine <player> [OPTIONS]
+
+A player is `engine` for the built-in one or `uci:<command>` for any UCI
+engine. The options of chess (--depth, --movetime, --eval-params, --book,
+--syzygy, --clock...) given after an --engine are for that engine, the ones
+before the first --engine for both.
+
+OPTIONS:
+    --name <name>         what to call the engine before it in the results
+                          and the PGN
+    --games <n>           how many games to play (default: 10)
+    --openings <file>     start the games from the positions in this file,
+                          one FEN or EPD per line; each is played twice, with
+                          the engines swapping colours (default: the usual
+                          starting position)
+    --pgn <file>          add every game to this PGN file
+    --sprt <elo0,elo1>    stop once it's clear whether the first engine is
+                          elo1 better (H1) or only elo0 (H0), with 5% chances
+                          of getting it wrong
+    --max-moves <n>       call it a draw after this many moves, 0 for never
+                          (default: 150)
+    --adjudicate <cp>     call it a win once both engines think one side is
+                          this many centipawns up for four moves, 0 for never
+                          (default: 1000)
+    -h, --help            print this and exit
+
+Repetitions and the fifty-move rule end the games too.
+";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("chess-match: {}", err);
+        process::exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let mut shared = Config::default();
+    let mut contestants: Vec<Contestant> = Vec::new();
+    let mut games = 10;
+    let mut openings = vec![Chess::default()];
+    let mut pgn = None;
+    let mut sprt = None;
+    let mut rules = Adjudication::default();
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        if arg == "-h" || arg == "--help" {
+            print!("{}", HELP);
+            return Ok(());
+        }
+
+        let key = match arg.strip_prefix("--") {
+            Some(key) => key.to_owned(),
+            None => return Err(Error::Config(format!("unexpected argument: {}", arg))),
+        };
+        let value = args
+            .next()
+            .ok_or_else(|| Error::Config(format!("--{} needs a value", key)))?;
+        let invalid = || Error::Config(format!("invalid value for --{}: {}", key, value));
+
+        match key.as_str() {
+            "engine" => {
+                let spec = PlayerSpec::parse(&value)?;
+                contestants.push(Contestant {
+                    name: value.clone(),
+                    spec,
+                    config: shared.clone(),
+                });
+            }
+            "name" => match contestants.last_mut() {
+                Some(contestant) => contestant.name = value,
+                None => return Err(Error::Config("--name goes after an --engine".to_owned())),
+            },
+            "games" => games = value.parse().map_err(|_| invalid())?,
+            "openings" => openings = tournament::load_openings(&PathBuf::from(&value))?,
+            "pgn" => pgn = Some(PathBuf::from(value)),
+            "sprt" => {
+                let (elo0, elo1) = value.split_once(',').ok_or_else(invalid)?;
+                sprt = Some(Sprt {
+                    elo0: elo0.trim().parse().map_err(|_| invalid())?,
+                    elo1: elo1.trim().parse().map_err(|_| invalid())?,
+                    alpha: 0.05,
+                    beta: 0.05,
+                });
+            }
+            "max-moves" => rules.max_moves = value.parse().map_err(|_| invalid())?,
+            "adjudicate" => rules.win_score = value.parse().map_err(|_| invalid())?,
+            _ => {
+                let config = match contestants.last_mut() {
+                    Some(contestant) => &mut contestant.config,
+                    None => &mut shared,
+                };
+                config
+                    .set(&key, &value)
+                    .map_err(|e| Error::Config(format!("--{}", e)))?;
+            }
+        }
+    }
+
+    if contestants.len() != 2 {
+        return Err(Error::Config("a match needs two --engine".to_owned()));
+    }
+    if openings.is_empty() {
+        return Err(Error::Config(
+            "no positions in the opening suite".to_owned(),
+        ));
+    }
+    if contestants[0].name == contestants[1].name {
+        contestants[0].name.push_str(" (1)");
+        contestants[1].name.push_str(" (2)");
+    }
+    for contestant in &contestants {
+        if contestant.spec == PlayerSpec::Human {
+            return Err(Error::Config("humans don't play in matches".to_owned()));
+        }
+    }
+
+    let (first, second) = (&contestants[0], &contestants[1]);
+    println!("{} vs {}, {} games", first.name, second.name, games);
+
+    let mut tally = Tally::default();
+    for game in 0..games {
+        let start = &openings[(game / 2) % openings.len()];
+        // the first engine plays white in the even games
+        let first_color = if game % 2 == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        let (white, black) = first_color.fold((first, second), (second, first));
+
+        let played = tournament::play_game(white, black, start, &rules, game + 1)?;
+        tally.add(played.outcome, first_color);
+
+        if let Some(path) = &pgn {
+            let mut file = OpenOptions::new()
+                .create(true)
+                .append(true)
+                .open(path)
+                .map_err(|e| Error::io(path.display(), e))?;
+            writeln!(file, "{}", played.pgn).map_err(|e| Error::io(path.display(), e))?;
+        }
+
+        let elo = match tally.elo() {
+            Some((diff, margin)) => format!("{:+.1} +/- {:.1}", diff, margin),
+            None => "-".to_owned(),
+        };
+        print!(
+            "game {}: {} - {}, {} ({}), {}-{}-{}, elo {}",
+            game + 1,
+            white.name,
+            black.name,
+            chess::pgn::result(Some(played.outcome)),
+            played.reason,
+            tally.wins,
+            tally.losses,
+            tally.draws,
+            elo
+        );
+
+        match sprt {
+            Some(sprt) => {
+                let (lower, upper) = sprt.bounds();
+                println!(
+                    ", llr {:.2} ({:.2}, {:.2})",
+                    tally.llr(sprt.elo0, sprt.elo1),
+                    lower,
+                    upper
+                );
+                if let Some(verdict) = sprt.verdict(&tally) {
+                    let (accepted, rejected) = match verdict {
+                        Verdict::H0 => (sprt.elo0, sprt.elo1),
+                        Verdict::H1 => (sprt.elo1, sprt.elo0),
+                    };
+                    println!(
+                        "SPRT: {:?} accepted, {} is closer to {} elo better than to {}",
+                        verdict, first.name, accepted, rejected
+                    );
+                    break;
+                }
+            }
+            None => println!(),
+        }
+    }
+
+    println!(
+        "\n{}: {} wins, {} losses, {} draws, score {:.1}%",
+        first.name,
+        tally.wins,
+        tally.losses,
+        tally.draws,
+        tally.score() * 100.0
+    );
+    if let Some((diff, margin)) = tally.elo() {
+        println!("elo difference: {:+.1} +/- {:.1} (95%)", diff, margin);
+    }
+
+    Ok(())
+}
@@ -0,0 +1,38 @@
+extern crate chess;
+
+use std::env;
+use std::io::{self, IsTerminal};
+use std::process;
+
+use chess::config::{self, Command, Config};
+use chess::error::Error;
+use chess::tui;
+
+// The game in a terminal, for when there's no SDL around (over SSH, say).
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("chess-tui: {}", err);
+        process::exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let config = match Config::from_args(env::args().skip(1))? {
+        Command::Play(config) => config,
+        Command::Help => {
+            println!(
+                "chess-tui takes the same options as chess, and ignores the graphical ones.\n"
+            );
+            print!("{}", config::HELP);
+            return Ok(());
+        }
+    };
+
+    let stdin = io::stdin();
+    let stdout = io::stdout();
+
+    // https://no-color.org
+    let colors = stdout.is_terminal() && env::var_os("NO_COLOR").is_none();
+
+    tui::run(&config, stdin.lock(), stdout.lock(), colors)
+}
@@ -0,0 +1,119 @@
+extern crate chess;
+
+use std::env;
+use std::path::PathBuf;
+use std::process;
+use std::time::Instant;
+
+use chess::error::Error;
+use chess::params::EvalParams;
+use chess::tune;
+
+const HELP: &str = "\
+chess-tune - fits the engine's evaluation to the results of real games.
+
+USAGE:
+    chess-tune <positions> [OPTIONS]
+
+<positions> is an EPD or FEN file with one position per line and the result
+of its game after it, as c9 \"1-0\"; or [0.5] or [1/2-1/2].
+
+OPTIONS:
+    --output <file>   write the tuned parameters here, after every pass
+                      (default: tuned.params)
+    --params <file>   start from these parameters instead of the built-in ones
+    --passes <n>      stop after this many passes (default: 100)
+    --k <k>           the sigmoid's scaling (default: the one that fits the
+                      starting parameters best)
+    --quiescence      play out the captures in every position first
+    -h, --help        print this and exit
+
+The output has one `name = [numbers]` line per parameter, with the whole
+table on that line, and is read by chess --eval-params and the EvalParams
+engine option.
+";
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("chess-tune: {}", err);
+        process::exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let mut positions = None;
+    let mut output = PathBuf::from("tuned.params");
+    let mut params = EvalParams::default();
+    let mut passes = 100;
+    let mut k = None;
+    let mut quiescence = false;
+
+    let mut args = env::args().skip(1);
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| Error::Config(format!("{} needs a value", arg)))
+        };
+        let invalid = |value: &str| Error::Config(format!("invalid value for {}: {}", arg, value));
+
+        match arg.as_str() {
+            "-h" | "--help" => {
+                print!("{}", HELP);
+                return Ok(());
+            }
+            "--output" => output = PathBuf::from(value()?),
+            "--params" => params = EvalParams::load(&PathBuf::from(value()?))?,
+            "--passes" => {
+                let value = value()?;
+                passes = value.parse().map_err(|_| invalid(&value))?;
+            }
+            "--k" => {
+                let value = value()?;
+                k = Some(value.parse().map_err(|_| invalid(&value))?);
+            }
+            "--quiescence" => quiescence = true,
+            _ if arg.starts_with("--") || positions.is_some() => {
+                return Err(Error::Config(format!("unexpected argument: {}", arg)))
+            }
+            _ => positions = Some(PathBuf::from(arg)),
+        }
+    }
+
+    let positions =
+        positions.ok_or_else(|| Error::Config("which positions? see --help".to_owned()))?;
+    let samples = tune::load_samples(&positions, quiescence)?;
+    if samples.is_empty() {
+        return Err(Error::Config(format!(
+            "no positions in {}",
+            positions.display()
+        )));
+    }
+
+    let k = k.unwrap_or_else(|| tune::best_k(&samples, &params));
+    println!(
+        "{} positions, k = {:.3}, error {:.6}",
+        samples.len(),
+        k,
+        tune::error(&samples, &params, k)
+    );
+
+    let start = Instant::now();
+    let mut saved = Ok(());
+    tune::tune(&samples, &params, k, passes, |pass, params| {
+        println!(
+            "pass {}: error {:.6}, {} parameters changed, {:.1}s",
+            pass.number,
+            pass.error,
+            pass.changed,
+            start.elapsed().as_secs_f64()
+        );
+        // so stopping it halfway loses nothing
+        if saved.is_ok() {
+            saved = params.save(&output);
+        }
+    });
+    saved?;
+
+    println!("saved to {}", output.display());
+    Ok(())
+}
@@ -0,0 +1,416 @@
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use shakmaty::Color;
+
+use crate::book::Selection;
+use crate::error::Error;
+use crate::params::EvalParams;
+use crate::player::PlayerSpec;
+use crate::skill::{self, Skill};
+
+// Every setting of the game. They start at their defaults, then the config
+// file is applied on top of them, and then the command line on top of that.
+// Config file keys and command-line options share their names, so
+// `depth = 4` in the file is the same as `--depth 4`.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Config {
+    // how many plies the AI looks ahead
+    pub depth: u32,
+    // if set, the AI keeps deepening its search (up to `depth`) for about this many milliseconds
+    pub movetime: Option<u64>,
+    // Polyglot opening book the engine plays its first moves from
+    pub book: Option<PathBuf>,
+    // plies into the game the book is used for
+    pub book_depth: u32,
+    pub book_selection: Selection,
+    // directories with Syzygy endgame tablebases for the engine
+    pub syzygy: Option<PathBuf>,
+    // a file with the weights of the engine's evaluation, instead of the built-in ones
+    pub eval_params: Option<PathBuf>,
+    // how well the built-in engine plays, from 1 to 20 (its best)
+    pub skill: u32,
+    // seconds each side gets and the seconds added after every move, off by default
+    pub clock: Option<(u64, u64)>,
+    // the side the human plays
+    pub human: Color,
+    // who plays each side, if not the human and the engine
+    pub white: Option<PlayerSpec>,
+    pub black: Option<PlayerSpec>,
+    pub fen: Option<String>,
+    pub theme: Option<String>,
+    pub sound: bool,
+    // the board is always square
+    pub window_size: u32,
+    // finished games are saved here as PGN
+    pub autosave: Option<PathBuf>,
+    pub assets: Option<PathBuf>,
+}
+
+impl Default for Config {
+    fn default() -> Config {
+        Config {
+            depth: 3,
+            movetime: None,
+            book: None,
+            book_depth: 20,
+            book_selection: Selection::Weighted,
+            syzygy: None,
+            eval_params: None,
+            skill: skill::MAX_LEVEL,
+            clock: None,
+            human: Color::White,
+            white: None,
+            black: None,
+            fen: None,
+            theme: None,
+            sound: true,
+            window_size: 600,
+            autosave: None,
+            assets: None,
+        }
+    }
+}
+
+pub const HELP: &str = "\
+chess - chess implemented in Rust.
+
+USAGE:
+    chess [OPTIONS]
+
+OPTIONS:
+    --depth <plies>       how many plies the AI looks ahead (default: 3)
+    --movetime <ms|off>   let the AI deepen its search, up to --depth, for about
+                          this many milliseconds per move (default: off)
+    --book <file|off>     play the engine's first moves from this Polyglot
+                          (.bin) opening book (default: off)
+    --book-depth <plies>  use the book for this many plies (default: 20)
+    --book-selection <how>
+                          weighted picks one of the book's moves at random,
+                          preferring the ones it rates higher; best always
+                          plays the highest rated one (default: weighted)
+    --syzygy <dir|off>    score endgames from the Syzygy tablebases (.rtbw
+                          and .rtbz files) in this directory, or several
+                          separated by ':' (default: off)
+    --eval-params <file|off>
+                          evaluate with the weights in this file instead of
+                          the built-in ones, one `name = [numbers]` per line
+                          like the config file (default: off)
+    --skill <level>       how well the AI plays, from 1 for a beginner to 20
+                          for its best; the lower, the shallower it looks and
+                          the more it plays moves a bit worse than the best,
+                          and now and then a real mistake (default: 20)
+    --elo <rating>        the skill level that plays at about this Elo, from
+                          600 to 1800
+    --clock <min+inc>     play with a clock: minutes per side plus seconds
+                          added after every move, like 5+3 (default: off)
+    --color <side>        the side you play, white or black (default: white)
+    --white <player>      who plays white: human, engine, uci:<command> to run
+                          a UCI engine, listen:<port> to wait for someone to
+                          connect over the network or connect:<host:port>
+    --black <player>      the same for black (default: human and engine, as
+                          set by --color)
+    --fen <fen>           start from this position instead of the usual one
+    --theme <name|file>   board theme: wood, green, blue, gray, one of your
+                          themes or a theme file (default: wood)
+    --sound <on|off>      play a sound on every move (default: on)
+    --window-size <px>    width and height of the window (default: 600)
+    --autosave <dir>      save every finished game in this directory as PGN
+    --assets <dir>        directory with the piece sprites
+    --config <file>       read the settings from this file instead of the
+                          default one
+    -h, --help            print this and exit
+
+Every option except --config and --help can also be set in the config file,
+~/.config/chess/chess.conf (or $XDG_CONFIG_HOME/chess/chess.conf), one
+`key = value` per line without the leading dashes:
+
+    # comments start with '#'
+    depth = 4
+    color = black
+    theme = green
+
+Options given on the command line override the config file.
+
+KEYS:
+    T    next theme
+    F    flip the board
+    C    show or hide the coordinates
+    E    show or hide how the engine scores the position
+    H    suggest a move, shown as an arrow; the game record notes it
+    + -  make the engine play better or worse
+    U    take back your last move
+    N    new game
+    R    resign
+    Tab  type a move (Nf3, exd5, O-O, e8=N or g1f3), Enter plays it,
+         Esc or Tab stops typing
+    Arrows and Enter or Space
+         move a cursor around the board, pick up a piece and put it down
+    Esc  quit
+";
+
+// What the command line asked for.
+pub enum Command {
+    Play(Box<Config>),
+    Help,
+}
+
+impl Config {
+    pub fn set(&mut self, key: &str, value: &str) -> Result<(), Error> {
+        let invalid = || Error::Config(format!("invalid value for {}: {}", key, value));
+
+        match key {
+            "depth" => {
+                self.depth = value.parse().map_err(|_| invalid())?;
+                if self.depth == 0 {
+                    return Err(invalid());
+                }
+            }
+            "movetime" => {
+                self.movetime = match value {
+                    "off" => None,
+                    _ => Some(value.parse().map_err(|_| invalid())?),
+                }
+            }
+            "book" => {
+                self.book = match value {
+                    "off" => None,
+                    _ => Some(PathBuf::from(value)),
+                }
+            }
+            "book-depth" => self.book_depth = value.parse().map_err(|_| invalid())?,
+            "book-selection" => {
+                self.book_selection = match value {
+                    "weighted" => Selection::Weighted,
+                    "best" => Selection::Best,
+                    _ => return Err(invalid()),
+                }
+            }
+            "syzygy" => {
+                self.syzygy = match value {
+                    "off" => None,
+                    _ => Some(PathBuf::from(value)),
+                }
+            }
+            "eval-params" => {
+                self.eval_params = match value {
+                    "off" => None,
+                    _ => Some(PathBuf::from(value)),
+                }
+            }
+            "skill" => {
+                self.skill = value.parse().map_err(|_| invalid())?;
+                if !(skill::MIN_LEVEL..=skill::MAX_LEVEL).contains(&self.skill) {
+                    return Err(invalid());
+                }
+            }
+            "elo" => self.skill = Skill::from_elo(value.parse().map_err(|_| invalid())?).level,
+            "clock" => {
+                let (minutes, increment) = value.split_once('+').unwrap_or((value, "0"));
+                let minutes: f64 = minutes.trim().parse().map_err(|_| invalid())?;
+                let increment = increment.trim().parse().map_err(|_| invalid())?;
+                if minutes <= 0.0 {
+                    return Err(invalid());
+                }
+                self.clock = Some(((minutes * 60.0) as u64, increment));
+            }
+            "color" => {
+                self.human = match value {
+                    "white" => Color::White,
+                    "black" => Color::Black,
+                    _ => return Err(invalid()),
+                }
+            }
+            "white" => self.white = Some(PlayerSpec::parse(value)?),
+            "black" => self.black = Some(PlayerSpec::parse(value)?),
+            "fen" => self.fen = Some(value.to_owned()),
+            "theme" => self.theme = Some(value.to_owned()),
+            "sound" => {
+                self.sound = match value {
+                    "on" | "true" | "yes" => true,
+                    "off" | "false" | "no" => false,
+                    _ => return Err(invalid()),
+                }
+            }
+            "window-size" => {
+                self.window_size = value.parse().map_err(|_| invalid())?;
+                if self.window_size < 8 {
+                    return Err(invalid());
+                }
+            }
+            "autosave" => self.autosave = Some(PathBuf::from(value)),
+            "assets" => self.assets = Some(PathBuf::from(value)),
+            _ => return Err(Error::Config(format!("unknown setting: {}", key))),
+        }
+
+        Ok(())
+    }
+
+    // Who plays white and who plays black: --white and --black if given,
+    // otherwise the human on their side and the engine on the other.
+    pub fn players(&self) -> (PlayerSpec, PlayerSpec) {
+        let (white, black) = self.human.fold(
+            (PlayerSpec::Human, PlayerSpec::Engine),
+            (PlayerSpec::Engine, PlayerSpec::Human),
+        );
+
+        (
+            self.white.clone().unwrap_or(white),
+            self.black.clone().unwrap_or(black),
+        )
+    }
+
+    // the evaluation parameters of --eval-params, or the built-in ones
+    pub fn load_eval_params(&self) -> Result<Rc<EvalParams>, Error> {
+        match &self.eval_params {
+            Some(path) => Ok(Rc::new(EvalParams::load(path)?)),
+            None => Ok(Rc::new(EvalParams::default())),
+        }
+    }
+
+    pub fn apply_file(&mut self, path: &Path) -> Result<(), Error> {
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path.display(), e))?;
+        let in_file = |e: Error| Error::Config(format!("{}: {}", path.display(), e));
+
+        for (key, value) in parse_kv(&text).map_err(in_file)? {
+            self.set(&key, &value).map_err(in_file)?;
+        }
+
+        Ok(())
+    }
+
+    // Reads the config file and then the command line (without the program name).
+    pub fn from_args<I: IntoIterator<Item = String>>(args: I) -> Result<Command, Error> {
+        let mut options = Vec::new();
+        let mut config_file = None;
+
+        let mut args = args.into_iter();
+        while let Some(arg) = args.next() {
+            if arg == "-h" || arg == "--help" {
+                return Ok(Command::Help);
+            }
+
+            let key = match arg.strip_prefix("--") {
+                Some(key) => key.to_owned(),
+                None => return Err(Error::Config(format!("unexpected argument: {}", arg))),
+            };
+            let value = args
+                .next()
+                .ok_or_else(|| Error::Config(format!("--{} needs a value", key)))?;
+
+            if key == "config" {
+                config_file = Some(PathBuf::from(value));
+            } else {
+                options.push((key, value));
+            }
+        }
+
+        let mut config = Config::default();
+
+        match config_file {
+            Some(path) => config.apply_file(&path)?,
+            None => {
+                // not having a config file is fine
+                if let Some(path) = config_path().filter(|path| path.is_file()) {
+                    config.apply_file(&path)?;
+                }
+            }
+        }
+
+        for (key, value) in options {
+            config
+                .set(&key, &value)
+                .map_err(|e| Error::Config(format!("--{}", e)))?;
+        }
+
+        Ok(Command::Play(Box::new(config)))
+    }
+}
+
+// $XDG_CONFIG_HOME/chess, usually ~/.config/chess
+pub fn config_dir() -> Option<PathBuf> {
+    match env::var_os("XDG_CONFIG_HOME") {
+        Some(path) => Some(PathBuf::from(path).join("chess")),
+        None => env::var_os("HOME").map(|home| PathBuf::from(home).join(".config/chess")),
+    }
+}
+
+pub fn config_path() -> Option<PathBuf> {
+    config_dir().map(|dir| dir.join("chess.conf"))
+}
+
+// `key = value` lines, ignoring blank lines and '#' comments.
+pub fn parse_kv(text: &str) -> Result<Vec<(String, String)>, Error> {
+    let mut pairs = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+
+        match line.find('=') {
+            Some(eq) => pairs.push((
+                line[..eq].trim().to_owned(),
+                line[eq + 1..].trim().to_owned(),
+            )),
+            None => {
+                return Err(Error::Config(format!(
+                    "line {}: expected `key = value`",
+                    i + 1
+                )))
+            }
+        }
+    }
+
+    Ok(pairs)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_command_line_overrides_config_file() {
+        let path = env::temp_dir().join(format!("chess-test-{}.conf", std::process::id()));
+        fs::write(
+            &path,
+            "# test\ndepth = 5\nmovetime = 500\ncolor = black\nsound = off\n",
+        )
+        .unwrap();
+
+        let args = vec![
+            "--config",
+            path.to_str().unwrap(),
+            "--depth",
+            "2",
+            "--movetime",
+            "off",
+            "--clock",
+            "0.5+2",
+            "--white",
+            "uci:stockfish",
+            "--elo",
+            "1200",
+        ];
+        let config = match Config::from_args(args.into_iter().map(String::from)) {
+            Ok(Command::Play(config)) => *config,
+            _ => panic!("expected a config"),
+        };
+        fs::remove_file(&path).unwrap();
+
+        assert_eq!(config.depth, 2);
+        assert_eq!(config.movetime, None);
+        assert_eq!(config.human, Color::Black);
+        assert!(!config.sound);
+        assert_eq!(config.window_size, 600);
+        assert_eq!(config.clock, Some((30, 2)));
+        assert_eq!(config.skill, 11);
+        assert_eq!(
+            config.players(),
+            (PlayerSpec::Uci("stockfish".to_owned()), PlayerSpec::Human)
+        );
+    }
+}
@@ -0,0 +1,874 @@
+extern crate shakmaty;
+
+use shakmaty::{CastlingSide, Chess, Color, Move, Outcome, Position, Role, Setup, Square};
+
+use std::collections::VecDeque;
+use std::fmt;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::hint::{self, Hint, HintSearch};
+use crate::notation;
+use crate::params::EvalParams;
+use crate::pgn;
+use crate::player::{GameState, Player, Turn};
+
+// What a front-end tells the controller, already translated into squares.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Input {
+    // click on a square: picks up one of our pieces, or drops the one we hold
+    Select(Square),
+    // let go of the piece we hold on a square
+    Drop(Square),
+    // which piece the pawn waiting on the last rank becomes
+    Promote(Role),
+    // a whole move at once, for front-ends where moves are typed
+    Play(Move),
+    Undo,
+    Resign,
+    NewGame,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EndReason {
+    Checkmate,
+    Stalemate,
+    InsufficientMaterial,
+    Resignation,
+    Timeout,
+    // a player stopped working: an engine crashed, the network went down...
+    Forfeit,
+    // decided from outside the game, like a match runner calling a draw
+    Adjudication,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct GameResult {
+    pub outcome: Outcome,
+    pub reason: EndReason,
+}
+
+// "Checkmate, white wins (1-0)"
+impl fmt::Display for GameResult {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let reason = match self.reason {
+            EndReason::Checkmate => "Checkmate",
+            EndReason::Stalemate => "Stalemate",
+            EndReason::InsufficientMaterial => "Insufficient material",
+            EndReason::Resignation => "Resignation",
+            EndReason::Timeout => "Time out",
+            EndReason::Forfeit => "Forfeit",
+            EndReason::Adjudication => "Adjudication",
+        };
+
+        match self.outcome {
+            Outcome::Decisive { winner } => {
+                write!(f, "{}, {} wins", reason, winner.fold("white", "black"))?
+            }
+            Outcome::Draw => write!(f, "{}, draw", reason)?,
+        }
+
+        write!(f, " ({})", pgn::result(Some(self.outcome)))
+    }
+}
+
+// What happened, for the front-end to show or play a sound for.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Event {
+    Moved { m: Move, san: String, by: Color },
+    Illegal(Square),
+    // a pawn reached the last rank, answer with `Input::Promote`
+    PromotionNeeded { from: Square, to: Square },
+    Undone,
+    NewGame,
+    // what went wrong with a player, just before it forfeits
+    PlayerError(Color, String),
+    GameOver(GameResult),
+    // the hint asked for with `hint`
+    Hint(Hint),
+}
+
+#[derive(Clone, Debug)]
+pub struct Clock {
+    remaining: [Duration; 2],
+    increment: Duration,
+    // when the side to move started thinking
+    running_since: Option<Instant>,
+}
+
+impl Clock {
+    pub fn new(time: Duration, increment: Duration) -> Clock {
+        Clock {
+            remaining: [time, time],
+            increment,
+            running_since: None,
+        }
+    }
+
+    pub fn remaining(&self, color: Color, turn: Color, now: Instant) -> Duration {
+        let remaining = self.remaining[index(color)];
+
+        match self.running_since {
+            Some(since) if color == turn => remaining.checked_sub(now - since).unwrap_or_default(),
+            _ => remaining,
+        }
+    }
+
+    fn start(&mut self, now: Instant) {
+        self.running_since = Some(now);
+    }
+
+    // the side that just moved stops its clock and gets its increment
+    fn press(&mut self, color: Color, now: Instant) {
+        let left = self.remaining(color, color, now);
+        self.remaining[index(color)] = left + self.increment;
+        self.running_since = Some(now);
+    }
+
+    fn stop(&mut self, turn: Color, now: Instant) {
+        self.remaining[index(turn)] = self.remaining(turn, turn, now);
+        self.running_since = None;
+    }
+}
+
+// 4:05
+pub fn format_clock(remaining: Duration) -> String {
+    let secs = remaining.as_secs();
+    format!("{}:{:02}", secs / 60, secs % 60)
+}
+
+struct Ply {
+    // the position before the move
+    before: Chess,
+    m: Move,
+    san: String,
+    // the move suggested to the human just before, if they asked
+    hint: Option<String>,
+}
+
+// Everything about a game except how it's shown: the position and its
+// history, who plays which side, the clocks, the piece the human is holding
+// and whether the game is over. Front-ends feed it `Input`s, call `update`
+// every frame and read `Event`s back.
+pub struct GameController {
+    start: Chess,
+    position: Chess,
+    history: Vec<Ply>,
+    players: [Box<dyn Player>; 2],
+    // the time control of new games
+    time_control: Option<(Duration, Duration)>,
+    clock: Option<Clock>,
+    selected: Option<Square>,
+    pending_promotion: Option<(Square, Square)>,
+    result: Option<GameResult>,
+    events: VecDeque<Event>,
+    // the hint given in the current position, in SAN
+    hint: Option<String>,
+    // the one still being worked out, and how long `update` may spend on it
+    hint_search: Option<HintSearch>,
+    hint_slice: Option<Duration>,
+    // a player that isn't ready to start yet, nobody moves until it is
+    waiting: Option<Color>,
+}
+
+impl GameController {
+    pub fn new(start: Chess, white: Box<dyn Player>, black: Box<dyn Player>) -> GameController {
+        let mut controller = GameController {
+            position: start.clone(),
+            start,
+            history: Vec::new(),
+            players: [white, black],
+            time_control: None,
+            clock: None,
+            selected: None,
+            pending_promotion: None,
+            result: None,
+            events: VecDeque::new(),
+            hint: None,
+            hint_search: None,
+            // the browser mustn't block, the same as the engine
+            hint_slice: if cfg!(target_os = "emscripten") {
+                Some(Duration::from_millis(30))
+            } else {
+                None
+            },
+            waiting: None,
+        };
+        controller.wait_for_players();
+        controller
+    }
+
+    // every side gets `time`, plus `increment` after each move
+    pub fn with_clock(mut self, time: Duration, increment: Duration) -> GameController {
+        self.time_control = Some((time, increment));
+        self.clock = Some(Clock::new(time, increment));
+        self
+    }
+
+    pub fn position(&self) -> &Chess {
+        &self.position
+    }
+
+    pub fn start_position(&self) -> &Chess {
+        &self.start
+    }
+
+    pub fn player(&self, color: Color) -> &dyn Player {
+        self.players[index(color)].as_ref()
+    }
+
+    pub fn player_mut(&mut self, color: Color) -> &mut dyn Player {
+        self.players[index(color)].as_mut()
+    }
+
+    pub fn is_human_turn(&self) -> bool {
+        self.result.is_none()
+            && self.waiting.is_none()
+            && self.player(self.position.turn()).is_human()
+    }
+
+    // the player the game waits for before it starts, see `Player::ready`
+    pub fn waiting_for(&self) -> Option<Color> {
+        self.waiting
+    }
+
+    // not against someone over the network
+    pub fn can_take_back(&self) -> bool {
+        self.players.iter().all(|player| player.can_take_back())
+    }
+
+    pub fn selected(&self) -> Option<Square> {
+        self.selected
+    }
+
+    pub fn pending_promotion(&self) -> Option<(Square, Square)> {
+        self.pending_promotion
+    }
+
+    pub fn last_move(&self) -> Option<&Move> {
+        self.history.last().map(|ply| &ply.m)
+    }
+
+    // every move so far, in SAN
+    pub fn moves(&self) -> Vec<&str> {
+        self.history.iter().map(|ply| ply.san.as_str()).collect()
+    }
+
+    pub fn result(&self) -> Option<GameResult> {
+        self.result
+    }
+
+    pub fn remaining_time(&self, color: Color) -> Option<Duration> {
+        self.clock
+            .as_ref()
+            .map(|clock| clock.remaining(color, self.position.turn(), Instant::now()))
+    }
+
+    // squares the selected piece can go to, for highlighting
+    pub fn targets(&self) -> Vec<Square> {
+        match self.selected {
+            Some(from) => self
+                .position
+                .legals()
+                .iter()
+                .filter(|m| m.from() == Some(from))
+                .map(destination)
+                .collect(),
+            None => Vec::new(),
+        }
+    }
+
+    pub fn poll_event(&mut self) -> Option<Event> {
+        self.events.pop_front()
+    }
+
+    pub fn handle(&mut self, input: Input) {
+        match input {
+            Input::Select(square) => self.select(square),
+            Input::Drop(square) => self.drop(square),
+            Input::Promote(role) => self.promote(role),
+            Input::Play(m) => {
+                self.selected = None;
+                self.pending_promotion = None;
+
+                if !self.is_human_turn() {
+                    return;
+                }
+                if self.position.is_legal(&m) {
+                    self.play(m);
+                } else {
+                    self.events.push_back(Event::Illegal(m.to()));
+                }
+            }
+            Input::Undo => self.undo(),
+            Input::Resign => self.resign(),
+            Input::NewGame => self.new_game(),
+        }
+    }
+
+    // Asks the player to move if it's not a human, works on a hint if one was
+    // asked for and watches the clocks.
+    // The built-in engine searches right here, so this can take a while.
+    pub fn update(&mut self) {
+        if self.result.is_some() {
+            return;
+        }
+
+        if self.waiting.is_some() && !self.wait_for_players() {
+            return;
+        }
+
+        self.think_about_hint();
+
+        let turn = self.position.turn();
+        let now = Instant::now();
+
+        if let Some(clock) = &mut self.clock {
+            if clock.running_since.is_none() {
+                clock.start(now);
+            }
+
+            if clock.remaining(turn, turn, now) == Duration::from_secs(0) {
+                // you can't lose on time against a lone king
+                let outcome = if self.position.has_insufficient_material(!turn) {
+                    Outcome::Draw
+                } else {
+                    Outcome::Decisive { winner: !turn }
+                };
+                self.finish(outcome, EndReason::Timeout);
+                return;
+            }
+        }
+
+        if self.player(turn).is_human() || self.position.legals().is_empty() {
+            return;
+        }
+
+        let moves: Vec<Move> = self.history.iter().map(|ply| ply.m.clone()).collect();
+        let game = GameState {
+            start: &self.start,
+            moves: &moves,
+            position: &self.position,
+        };
+
+        match self.players[index(turn)].poll(&game) {
+            Ok(Turn::Thinking) => {}
+            Ok(Turn::Move(m)) => self.play(m),
+            Ok(Turn::Resign) => {
+                self.finish(Outcome::Decisive { winner: !turn }, EndReason::Resignation)
+            }
+            Err(err) => {
+                self.events
+                    .push_back(Event::PlayerError(turn, err.to_string()));
+                self.finish(Outcome::Decisive { winner: !turn }, EndReason::Forfeit);
+            }
+        }
+    }
+
+    // whether both players are ready, ending the game if one can't be
+    fn wait_for_players(&mut self) -> bool {
+        for &color in &[Color::White, Color::Black] {
+            match self.players[index(color)].ready() {
+                Ok(true) => {}
+                Ok(false) => {
+                    self.waiting = Some(color);
+                    return false;
+                }
+                Err(err) => {
+                    self.events
+                        .push_back(Event::PlayerError(color, err.to_string()));
+                    self.finish(Outcome::Decisive { winner: !color }, EndReason::Forfeit);
+                    return false;
+                }
+            }
+        }
+        self.waiting = None;
+        true
+    }
+
+    // Starts working out the move the engine would play in the human's
+    // place, false when it's not a human's turn. `update` comes back with
+    // `Event::Hint`, and the game record notes it next to the move played
+    // after it.
+    pub fn hint(&mut self, params: &Rc<EvalParams>) -> bool {
+        if !self.is_human_turn() || self.position.legals().is_empty() {
+            return false;
+        }
+
+        self.hint_search = Some(HintSearch::new(
+            &self.position,
+            hint::DEPTH,
+            Rc::clone(params),
+        ));
+        true
+    }
+
+    // asked for and not there yet
+    pub fn hint_pending(&self) -> bool {
+        self.hint_search.is_some()
+    }
+
+    fn think_about_hint(&mut self) {
+        let search = match &mut self.hint_search {
+            Some(search) => search,
+            None => return,
+        };
+
+        let done = match self.hint_slice {
+            Some(slice) => {
+                let start = Instant::now();
+                loop {
+                    if search.step() {
+                        break true;
+                    }
+                    if start.elapsed() >= slice {
+                        break false;
+                    }
+                }
+            }
+            None => {
+                while !search.step() {}
+                true
+            }
+        };
+
+        if done {
+            if let Some(hint) = search.result() {
+                self.hint = Some(hint.pv[0].clone());
+                self.events.push_back(Event::Hint(hint));
+            }
+            self.hint_search = None;
+        }
+    }
+
+    // ends the game with `outcome` without it being played out
+    pub fn adjudicate(&mut self, outcome: Outcome) {
+        if self.result.is_none() {
+            self.finish(outcome, EndReason::Adjudication);
+        }
+    }
+
+    pub fn pgn(&self) -> String {
+        let fen = notation::to_fen(&self.start);
+        pgn::write(
+            &self.player(Color::White).name(),
+            &self.player(Color::Black).name(),
+            if fen == notation::STARTING_FEN {
+                None
+            } else {
+                Some(&fen)
+            },
+            self.start.fullmoves(),
+            self.start.turn() == Color::Black,
+            &self
+                .history
+                .iter()
+                .map(|ply| match &ply.hint {
+                    Some(hint) => format!("{} {{hint: {}}}", ply.san, hint),
+                    None => ply.san.clone(),
+                })
+                .collect::<Vec<_>>(),
+            pgn::result(self.result.map(|result| result.outcome)),
+        )
+    }
+
+    fn select(&mut self, square: Square) {
+        self.pending_promotion = None;
+
+        if !self.is_human_turn() {
+            return;
+        }
+
+        // clicking a target of the piece we hold moves it there
+        if let Some(from) = self.selected {
+            if from != square && self.targets().contains(&square) {
+                self.drop(square);
+                return;
+            }
+        }
+
+        let ours = self.position.board().color_at(square) == Some(self.position.turn());
+        self.selected = if ours { Some(square) } else { None };
+    }
+
+    fn drop(&mut self, to: Square) {
+        let from = match self.selected.take() {
+            Some(from) if from != to && self.is_human_turn() => from,
+            // let go where we picked it up, keep holding it
+            Some(from) if from == to => {
+                self.selected = Some(from);
+                return;
+            }
+            _ => return,
+        };
+
+        let candidates: Vec<Move> = self
+            .position
+            .legals()
+            .into_iter()
+            .filter(|m| m.from() == Some(from) && (m.to() == to || destination(m) == to))
+            .collect();
+
+        match candidates.first() {
+            None => self.events.push_back(Event::Illegal(to)),
+            Some(m) if m.promotion().is_some() => {
+                self.pending_promotion = Some((from, m.to()));
+                self.events
+                    .push_back(Event::PromotionNeeded { from, to: m.to() });
+            }
+            Some(m) => {
+                let m = m.clone();
+                self.play(m);
+            }
+        }
+    }
+
+    fn promote(&mut self, role: Role) {
+        let (from, to) = match self.pending_promotion.take() {
+            Some(squares) => squares,
+            None => return,
+        };
+
+        let m = self
+            .position
+            .legals()
+            .into_iter()
+            .find(|m| m.from() == Some(from) && m.to() == to && m.promotion() == Some(role));
+
+        match m {
+            Some(m) => self.play(m),
+            None => self.events.push_back(Event::Illegal(to)),
+        }
+    }
+
+    // Takes back moves until it's a human's turn again, so against the
+    // computer this undoes its reply too.
+    fn undo(&mut self) {
+        if self.history.is_empty() || !self.can_take_back() {
+            return;
+        }
+
+        self.selected = None;
+        self.pending_promotion = None;
+        self.result = None;
+        self.hint = None;
+        self.hint_search = None;
+
+        while let Some(ply) = self.history.pop() {
+            self.position = ply.before;
+            if self.player(self.position.turn()).is_human() {
+                break;
+            }
+        }
+
+        self.events.push_back(Event::Undone);
+    }
+
+    fn resign(&mut self) {
+        if self.result.is_some() {
+            return;
+        }
+
+        // the human resigns, or the side to move if both or none are human
+        let turn = self.position.turn();
+        let loser = if self.player(!turn).is_human() && !self.player(turn).is_human() {
+            !turn
+        } else {
+            turn
+        };
+
+        self.finish(Outcome::Decisive { winner: !loser }, EndReason::Resignation);
+    }
+
+    fn new_game(&mut self) {
+        if !self.can_take_back() {
+            return;
+        }
+
+        self.position = self.start.clone();
+        self.history.clear();
+        self.hint = None;
+        self.hint_search = None;
+        self.selected = None;
+        self.pending_promotion = None;
+        self.result = None;
+        self.clock = self
+            .time_control
+            .map(|(time, increment)| Clock::new(time, increment));
+
+        self.events.push_back(Event::NewGame);
+    }
+
+    fn play(&mut self, m: Move) {
+        let by = self.position.turn();
+        let san = notation::san(&self.position, &m);
+        let before = self.position.clone();
+
+        self.position = match before.clone().play(&m) {
+            Ok(position) => position,
+            Err(_) => {
+                self.events.push_back(Event::Illegal(m.to()));
+                return;
+            }
+        };
+        self.history.push(Ply {
+            before,
+            m: m.clone(),
+            san: san.clone(),
+            hint: self.hint.take(),
+        });
+        self.hint_search = None;
+
+        if let Some(clock) = &mut self.clock {
+            clock.press(by, Instant::now());
+        }
+
+        let before = &self.history.last().unwrap().before;
+        self.players[index(!by)].opponent_moved(before, &m);
+
+        self.events.push_back(Event::Moved { m, san, by });
+
+        if let Some(outcome) = self.position.outcome() {
+            let reason = if self.position.is_checkmate() {
+                EndReason::Checkmate
+            } else if self.position.is_stalemate() {
+                EndReason::Stalemate
+            } else {
+                EndReason::InsufficientMaterial
+            };
+            self.finish(outcome, reason);
+        }
+    }
+
+    fn finish(&mut self, outcome: Outcome, reason: EndReason) {
+        let turn = self.position.turn();
+        if let Some(clock) = &mut self.clock {
+            clock.stop(turn, Instant::now());
+        }
+
+        self.selected = None;
+        self.pending_promotion = None;
+        self.hint_search = None;
+
+        let result = GameResult { outcome, reason };
+        self.result = Some(result);
+        for player in &mut self.players {
+            player.game_over(result);
+        }
+        self.events.push_back(Event::GameOver(result));
+    }
+}
+
+// white first, like the arguments of `GameController::new`
+fn index(color: Color) -> usize {
+    color.fold(0, 1)
+}
+
+// Castling is a king move onto its own rook, but it's shown and clicked
+// as the king going to its destination (g1 or c1).
+fn destination(m: &Move) -> Square {
+    match (m, m.castling_side()) {
+        (Move::Castle { king, .. }, Some(side)) => {
+            let file = match side {
+                CastlingSide::KingSide => shakmaty::File::G,
+                CastlingSide::QueenSide => shakmaty::File::C,
+            };
+            Square::from_coords(file, king.rank())
+        }
+        _ => m.to(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::error::Error;
+    use crate::player::{Engine, Human};
+    use std::cell::Cell;
+
+    fn human_vs_human(fen: &str) -> GameController {
+        let start = notation::position_from_fen(fen).unwrap();
+        GameController::new(start, Box::new(Human), Box::new(Human))
+    }
+
+    fn events(controller: &mut GameController) -> Vec<Event> {
+        let mut events = Vec::new();
+        while let Some(event) = controller.poll_event() {
+            events.push(event);
+        }
+        events
+    }
+
+    #[test]
+    fn test_select_and_drop() {
+        let mut controller = human_vs_human(notation::STARTING_FEN);
+
+        // black's pieces can't be picked up by white
+        controller.handle(Input::Select(Square::E7));
+        assert_eq!(controller.selected(), None);
+
+        controller.handle(Input::Select(Square::E2));
+        assert_eq!(controller.targets().len(), 2);
+        controller.handle(Input::Drop(Square::E5));
+        assert_eq!(events(&mut controller), vec![Event::Illegal(Square::E5)]);
+
+        controller.handle(Input::Select(Square::G1));
+        controller.handle(Input::Select(Square::F3));
+        assert_eq!(controller.moves(), vec!["Nf3"]);
+        assert_eq!(controller.position().turn(), Color::Black);
+    }
+
+    #[test]
+    fn test_castling_and_promotion() {
+        let mut controller = human_vs_human("4k3/1P6/8/8/8/8/8/4K2R w K - 0 1");
+
+        controller.handle(Input::Select(Square::E1));
+        controller.handle(Input::Drop(Square::G1));
+        assert_eq!(controller.moves(), vec!["O-O"]);
+
+        controller.handle(Input::Select(Square::E8));
+        controller.handle(Input::Drop(Square::D8));
+
+        controller.handle(Input::Select(Square::B7));
+        controller.handle(Input::Drop(Square::B8));
+        assert_eq!(
+            controller.pending_promotion(),
+            Some((Square::B7, Square::B8))
+        );
+
+        controller.handle(Input::Promote(Role::Knight));
+        assert_eq!(controller.moves(), vec!["O-O", "Kd8", "b8=N"]);
+    }
+
+    #[test]
+    fn test_castling_by_clicking() {
+        let mut controller = human_vs_human("r3k3/8/8/8/8/8/8/4K2R w Kq - 0 1");
+
+        controller.handle(Input::Select(Square::E1));
+        assert!(controller.targets().contains(&Square::G1));
+        assert!(!controller.targets().contains(&Square::H1));
+        controller.handle(Input::Select(Square::G1));
+        assert_eq!(controller.moves(), vec!["O-O"]);
+
+        controller.handle(Input::Select(Square::E8));
+        assert!(controller.targets().contains(&Square::C8));
+        controller.handle(Input::Select(Square::C8));
+        assert_eq!(controller.moves(), vec!["O-O", "O-O-O"]);
+        assert_eq!(controller.selected(), None);
+    }
+
+    // someone who takes a while to turn up, then plays nothing
+    struct Late(Rc<Cell<bool>>);
+
+    impl Player for Late {
+        fn name(&self) -> String {
+            "Late".to_owned()
+        }
+
+        fn ready(&mut self) -> Result<bool, Error> {
+            Ok(self.0.get())
+        }
+
+        fn poll(&mut self, _game: &GameState) -> Result<Turn, Error> {
+            Ok(Turn::Thinking)
+        }
+    }
+
+    #[test]
+    fn test_nobody_moves_before_everyone_is_ready() {
+        let arrived = Rc::new(Cell::new(false));
+        let late = Late(Rc::clone(&arrived));
+        let mut controller = GameController::new(Chess::default(), Box::new(Human), Box::new(late));
+        assert_eq!(controller.waiting_for(), Some(Color::Black));
+        assert!(!controller.is_human_turn());
+
+        controller.handle(Input::Select(Square::E2));
+        controller.handle(Input::Drop(Square::E4));
+        controller.update();
+        assert!(controller.moves().is_empty());
+
+        arrived.set(true);
+        controller.update();
+        assert_eq!(controller.waiting_for(), None);
+        controller.handle(Input::Select(Square::E2));
+        controller.handle(Input::Drop(Square::E4));
+        assert_eq!(controller.moves(), vec!["e4"]);
+    }
+
+    #[test]
+    fn test_undo_against_the_computer() {
+        let computer = Engine::new(1, None);
+        let mut controller =
+            GameController::new(Chess::default(), Box::new(Human), Box::new(computer));
+
+        controller.handle(Input::Select(Square::E2));
+        controller.handle(Input::Drop(Square::E4));
+        controller.update();
+        assert_eq!(controller.moves().len(), 2);
+        assert!(controller.is_human_turn());
+
+        // takes back the computer's reply and our move
+        controller.handle(Input::Undo);
+        assert!(controller.moves().is_empty());
+        assert!(controller.is_human_turn());
+    }
+
+    #[test]
+    fn test_hints_go_in_the_record() {
+        let computer = Engine::new(1, None);
+        let mut controller =
+            GameController::new(Chess::default(), Box::new(Human), Box::new(computer));
+        let params = Rc::new(EvalParams::default());
+
+        assert!(controller.hint(&params));
+        assert!(controller.hint_pending());
+        controller.update();
+        let hint = match events(&mut controller).pop() {
+            Some(Event::Hint(hint)) => hint,
+            other => panic!("no hint: {:?}", other),
+        };
+        assert!(!controller.hint_pending());
+        controller.handle(Input::Play(hint.m.clone()));
+        assert!(!controller.hint(&params));
+        controller.update();
+        let played = format!("1. {0} {{hint: {0}}} ", hint.pv[0]);
+        assert!(controller.pgn().contains(&played));
+
+        // taken back along with the move
+        controller.handle(Input::Undo);
+        controller.handle(Input::Select(Square::E2));
+        controller.handle(Input::Drop(Square::E4));
+        assert!(!controller.pgn().contains("hint"));
+    }
+
+    #[test]
+    fn test_game_over() {
+        // fool's mate
+        let mut controller = human_vs_human(notation::STARTING_FEN);
+        for (from, to) in &[
+            (Square::F2, Square::F3),
+            (Square::E7, Square::E5),
+            (Square::G2, Square::G4),
+            (Square::D8, Square::H4),
+        ] {
+            controller.handle(Input::Select(*from));
+            controller.handle(Input::Drop(*to));
+        }
+
+        let result = controller.result().unwrap();
+        assert_eq!(result.reason, EndReason::Checkmate);
+        assert_eq!(result.outcome.winner(), Some(Color::Black));
+        assert!(controller.pgn().ends_with("2. g4 Qh4# 0-1\n"));
+
+        controller.handle(Input::Resign);
+        assert_eq!(controller.result(), Some(result));
+
+        controller.handle(Input::NewGame);
+        assert_eq!(controller.result(), None);
+        controller.handle(Input::Resign);
+        assert_eq!(
+            controller.result().unwrap().outcome.winner(),
+            Some(Color::Black)
+        );
+    }
+}
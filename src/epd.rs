@@ -0,0 +1,146 @@
+extern crate shakmaty;
+
+use shakmaty::{Chess, Move};
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::notation;
+
+// A test position from an EPD suite (WAC, ECM, STS...): the moves that
+// solve it, or the ones to stay away from.
+#[derive(Clone, Debug)]
+pub struct EpdEntry {
+    pub game: Chess,
+    pub id: Option<String>,
+    // bm, any of them is right
+    pub best: Vec<Move>,
+    // am, anything else is right
+    pub avoid: Vec<Move>,
+}
+
+impl EpdEntry {
+    pub fn solved_by(&self, m: &Move) -> bool {
+        (self.best.is_empty() || self.best.contains(m)) && !self.avoid.contains(m)
+    }
+}
+
+// the operations after the position, split at the semicolons that aren't
+// in quotes, each as its opcode and its operands
+fn operations(text: &str) -> Vec<(String, Vec<String>)> {
+    let mut operations = Vec::new();
+    let mut words = Vec::new();
+    let mut word = String::new();
+    let mut quoted = false;
+
+    for c in text.chars().chain(Some(';')) {
+        match c {
+            '"' => quoted = !quoted,
+            ';' | ' ' | '\t' if !quoted => {
+                if !word.is_empty() {
+                    words.push(word.clone());
+                    word.clear();
+                }
+                if c == ';' && !words.is_empty() {
+                    let opcode = words.remove(0);
+                    operations.push((opcode, words.clone()));
+                    words.clear();
+                }
+            }
+            _ => word.push(c),
+        }
+    }
+
+    operations
+}
+
+// `<position> bm Qg6; id "WAC.001";` and the like, None for blank lines and
+// '#' comments. The moves are in SAN.
+pub fn parse_line(line: &str) -> Result<Option<EpdEntry>, Error> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    let fields: Vec<&str> = line.splitn(5, char::is_whitespace).collect();
+    if fields.len() < 4 {
+        return Err(Error::Fen {
+            fen: line.to_owned(),
+            message: "an EPD position needs four fields".to_owned(),
+        });
+    }
+    let game = notation::position_from_fen(&fields[..4].join(" "))?;
+
+    let (mut id, mut best, mut avoid) = (None, Vec::new(), Vec::new());
+    for (opcode, operands) in operations(fields.get(4).unwrap_or(&"")) {
+        let moves = || -> Result<Vec<Move>, Error> {
+            operands
+                .iter()
+                .map(|san| notation::parse_move(&game, san))
+                .collect()
+        };
+        match opcode.as_str() {
+            "bm" => best = moves()?,
+            "am" => avoid = moves()?,
+            "id" => id = operands.first().cloned(),
+            _ => {}
+        }
+    }
+
+    if best.is_empty() && avoid.is_empty() {
+        return Err(Error::Move(format!("no bm or am in: {}", line)));
+    }
+    Ok(Some(EpdEntry {
+        game,
+        id,
+        best,
+        avoid,
+    }))
+}
+
+pub fn load(path: &Path) -> Result<Vec<EpdEntry>, Error> {
+    let text = fs::read_to_string(path).map_err(|e| Error::io(path.display(), e))?;
+    let mut entries = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let in_file = |e: Error| Error::Config(format!("{}:{}: {}", path.display(), i + 1, e));
+        entries.extend(parse_line(line).map_err(in_file)?);
+    }
+
+    Ok(entries)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai;
+
+    #[test]
+    fn test_epd() {
+        // WAC.001
+        let entry = parse_line(
+            "2rr3k/pp3pp1/1nnqbN1p/3pN3/2pP4/2P3Q1/PPB4P/R4RK1 w - - bm Qg6; id \"WAC.001\";",
+        )
+        .unwrap()
+        .unwrap();
+        assert_eq!(entry.id.as_deref(), Some("WAC.001"));
+        assert_eq!(notation::san(&entry.game, &entry.best[0]), "Qg6");
+
+        let entry = parse_line("6k1/5ppp/8/8/8/8/8/R5K1 w - - bm Ra8#;")
+            .unwrap()
+            .unwrap();
+        let result = ai::search(2, &entry.game).unwrap();
+        assert!(entry.solved_by(&result.best_move));
+
+        let entry = parse_line("4k3/8/8/3q4/8/8/3R4/4K3 w - - am Rd3 Rd4; id \"take it\";")
+            .unwrap()
+            .unwrap();
+        assert_eq!(entry.avoid.len(), 2);
+        let result = ai::search(1, &entry.game).unwrap();
+        assert!(entry.solved_by(&result.best_move));
+
+        assert!(parse_line("# WAC").unwrap().is_none());
+        assert!(parse_line("4k3/8/8/3q4/8/8/3R4/4K3 w - - id \"x\";").is_err());
+    }
+}
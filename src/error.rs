@@ -0,0 +1,139 @@
+use std::error;
+use std::fmt;
+use std::io;
+use std::path::PathBuf;
+
+// Everything that can go wrong, with enough context to tell the user what
+// happened and, where we can, what to do about it.
+#[derive(Debug)]
+pub enum Error {
+    // SDL couldn't start, open the window or load an image
+    Sdl(String),
+    // sprites, piece sets and theme files
+    Asset {
+        path: Option<PathBuf>,
+        message: String,
+    },
+    // a bad command-line option, config file line or setting
+    Config(String),
+    Fen {
+        fen: String,
+        message: String,
+    },
+    // not a move, not legal, or it could be several moves
+    Move(String),
+    NoLegalMoves,
+    // a UCI engine that won't start or says something we don't understand
+    Engine {
+        name: String,
+        message: String,
+    },
+    // the other side of a network game
+    Network {
+        peer: String,
+        message: String,
+    },
+    Io {
+        context: String,
+        source: io::Error,
+    },
+}
+
+impl Error {
+    pub fn io<C: fmt::Display>(context: C, source: io::Error) -> Error {
+        Error::Io {
+            context: context.to_string(),
+            source,
+        }
+    }
+
+    pub fn asset<P: Into<PathBuf>, M: fmt::Display>(path: P, message: M) -> Error {
+        Error::Asset {
+            path: Some(path.into()),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn engine<N: fmt::Display, M: fmt::Display>(name: N, message: M) -> Error {
+        Error::Engine {
+            name: name.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    pub fn network<P: fmt::Display, M: fmt::Display>(peer: P, message: M) -> Error {
+        Error::Network {
+            peer: peer.to_string(),
+            message: message.to_string(),
+        }
+    }
+
+    // what the programs exit with, from BSD's sysexits.h
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::Config(_) => 64,
+            Error::Fen { .. } | Error::Move(_) | Error::NoLegalMoves => 65,
+            Error::Asset { .. } => 66,
+            Error::Sdl(_) | Error::Engine { .. } | Error::Network { .. } => 69,
+            Error::Io { .. } => 74,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Error::Sdl(message) => write!(f, "SDL: {}", message),
+            Error::Asset {
+                path: Some(path),
+                message,
+            } => write!(f, "{}: {}", path.display(), message),
+            Error::Asset {
+                path: None,
+                message,
+            } => write!(f, "{}", message),
+            Error::Config(message) => write!(f, "{}", message),
+            Error::Fen { fen, message } => write!(f, "invalid FEN {}: {}", fen, message),
+            Error::Move(message) => write!(f, "{}", message),
+            Error::NoLegalMoves => write!(f, "there are no legal moves"),
+            Error::Engine { name, message } => write!(f, "{}: {}", name, message),
+            Error::Network { peer, message } => write!(f, "{}: {}", peer, message),
+            Error::Io { context, source } => write!(f, "{}: {}", context, source),
+        }
+    }
+}
+
+impl error::Error for Error {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::config::Config;
+    use crate::{ai, notation};
+
+    #[test]
+    fn test_errors_from_user_input() {
+        let args = vec!["--depth".to_owned(), "zero".to_owned()];
+        let err = Config::from_args(args).err().unwrap();
+        assert!(err.to_string().contains("zero"));
+        assert_eq!(err.exit_code(), 64);
+
+        let err = notation::position_from_fen("not a fen").unwrap_err();
+        assert!(matches!(err, Error::Fen { .. }));
+        assert_eq!(err.exit_code(), 65);
+
+        // checkmated, there's nothing to search
+        let mated = notation::position_from_fen(
+            "rnb1kbnr/pppp1ppp/8/4p3/6Pq/5P2/PPPPP2P/RNBQKBNR w KQkq - 1 3",
+        )
+        .unwrap();
+        assert!(ai::search(3, &mated).is_none());
+    }
+}
@@ -0,0 +1,568 @@
+extern crate shakmaty;
+
+use shakmaty::{attacks, Bitboard, Board, Color, Role};
+
+use std::fmt;
+use std::ops::{Add, AddAssign, Mul, Neg, Sub};
+
+use crate::params::EvalParams;
+
+// A middlegame and an endgame score, blended by the phase of the game once
+// everything is added up.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Score {
+    pub mg: i32,
+    pub eg: i32,
+}
+
+impl Score {
+    pub const fn new(mg: i32, eg: i32) -> Score {
+        Score { mg, eg }
+    }
+
+    // `phase` out of PHASE_TOTAL, all of it is the middlegame
+    pub fn blend(self, phase: i32) -> i32 {
+        (self.mg * phase + self.eg * (PHASE_TOTAL - phase)) / PHASE_TOTAL
+    }
+}
+
+impl Add for Score {
+    type Output = Score;
+
+    fn add(self, other: Score) -> Score {
+        Score::new(self.mg + other.mg, self.eg + other.eg)
+    }
+}
+
+impl AddAssign for Score {
+    fn add_assign(&mut self, other: Score) {
+        *self = *self + other;
+    }
+}
+
+impl Sub for Score {
+    type Output = Score;
+
+    fn sub(self, other: Score) -> Score {
+        Score::new(self.mg - other.mg, self.eg - other.eg)
+    }
+}
+
+impl Neg for Score {
+    type Output = Score;
+
+    fn neg(self) -> Score {
+        Score::new(-self.mg, -self.eg)
+    }
+}
+
+impl Mul<i32> for Score {
+    type Output = Score;
+
+    fn mul(self, times: i32) -> Score {
+        Score::new(self.mg * times, self.eg * times)
+    }
+}
+
+// what the pieces left on the board count towards the middlegame, this
+// much with all of them there
+pub const PHASE_TOTAL: i32 = 24;
+
+// how much a piece counts towards the middlegame
+fn phase_weight(role: Role) -> i32 {
+    match role {
+        Role::Knight | Role::Bishop => 1,
+        Role::Rook => 2,
+        Role::Queen => 4,
+        Role::Pawn | Role::King => 0,
+    }
+}
+
+const FILE_A: u64 = 0x0101_0101_0101_0101;
+
+fn file_mask(file: usize) -> u64 {
+    FILE_A << file
+}
+
+fn adjacent_files(file: usize) -> u64 {
+    let left = if file > 0 { file_mask(file - 1) } else { 0 };
+    let right = if file < 7 { file_mask(file + 1) } else { 0 };
+    left | right
+}
+
+// the ranks above `rank`
+fn ranks_above(rank: usize) -> u64 {
+    if rank >= 7 {
+        0
+    } else {
+        !0u64 << (8 * (rank + 1))
+    }
+}
+
+// the squares white pawns on `pawns` attack
+fn pawn_attacks(pawns: u64) -> u64 {
+    ((pawns << 7) & !file_mask(7)) | ((pawns << 9) & !file_mask(0))
+}
+
+// the squares the pawns of `color` attack
+fn pawn_attacks_of(board: &Board, color: Color) -> u64 {
+    let pawns = (board.pawns() & board.by_color(color)).0;
+    color.fold(
+        pawn_attacks(pawns),
+        pawn_attacks(pawns.swap_bytes()).swap_bytes(),
+    )
+}
+
+// What the pawns alone are worth, for white's pawns `ours` against black's
+// `theirs`. Black's are scored by turning the board upside down first, so
+// everything here is as if going up the board.
+struct PawnStructure {
+    score: Score,
+    passed: u64,
+}
+
+fn pawn_structure(ours: u64, theirs: u64, params: &EvalParams) -> PawnStructure {
+    let mut score = Score::default();
+    let mut passed = 0;
+    let defended = pawn_attacks(ours);
+    let their_attacks = pawn_attacks(theirs.swap_bytes()).swap_bytes();
+
+    for square in Bitboard(ours) {
+        let square = usize::from(square);
+        let (file, rank) = (square % 8, square / 8);
+        let bit = 1u64 << square;
+        let ahead = ranks_above(rank);
+        let beside = adjacent_files(file) & (0xff << (8 * rank));
+
+        // the pawns behind on the same file are doubled with this one
+        if ours & file_mask(file) & !ahead & !bit & !(0xff << (8 * rank)) != 0 {
+            score += params.doubled;
+        }
+
+        let isolated = ours & adjacent_files(file) == 0;
+        if isolated {
+            score += params.isolated;
+        }
+
+        let connected = defended & bit != 0 || ours & beside != 0;
+        if connected {
+            score += params.connected;
+        }
+
+        // every pawn that could come to help is already past it, and the
+        // square in front is watched by a pawn of theirs
+        let stop = bit << 8;
+        if !isolated
+            && !connected
+            && ours & adjacent_files(file) & !ahead == 0
+            && their_attacks & stop != 0
+        {
+            score += params.backward;
+        }
+
+        let front_span = (file_mask(file) | adjacent_files(file)) & ahead;
+        if theirs & front_span == 0 && ours & file_mask(file) & ahead == 0 {
+            passed |= bit;
+            score += params.passed[rank];
+            // with another pawn there to defend it
+            if defended & bit != 0 {
+                score += Score::new(params.passed[rank].mg / 2, params.passed[rank].eg / 2);
+            }
+        }
+    }
+
+    PawnStructure { score, passed }
+}
+
+// the pawn structure and the passed pawns of black and white
+#[derive(Clone, Copy, Default)]
+struct PawnEntry {
+    white: u64,
+    black: u64,
+    scores: [Score; 2],
+    passed: [u64; 2],
+}
+
+fn pawn_entry(board: &Board, params: &EvalParams) -> PawnEntry {
+    let white = (board.pawns() & board.by_color(Color::White)).0;
+    let black = (board.pawns() & board.by_color(Color::Black)).0;
+    let ours = pawn_structure(white, black, params);
+    let theirs = pawn_structure(black.swap_bytes(), white.swap_bytes(), params);
+
+    PawnEntry {
+        white,
+        black,
+        scores: [theirs.score, ours.score],
+        passed: [theirs.passed.swap_bytes(), ours.passed],
+    }
+}
+
+// Pawns don't move much, so the same structures come up over and over in a
+// search: they're worked out once and kept here, by where the pawns are.
+// The scores are the ones of the parameters they were worked out with, so a
+// table only ever goes with one set of them.
+const PAWN_TABLE_SIZE: usize = 1 << 14;
+
+pub struct PawnTable {
+    entries: Vec<Option<PawnEntry>>,
+}
+
+impl PawnTable {
+    pub fn new() -> PawnTable {
+        PawnTable {
+            entries: vec![None; PAWN_TABLE_SIZE],
+        }
+    }
+
+    fn get(&mut self, board: &Board, params: &EvalParams) -> PawnEntry {
+        let white = (board.pawns() & board.by_color(Color::White)).0;
+        let black = (board.pawns() & board.by_color(Color::Black)).0;
+        let key = (white ^ black.rotate_left(32)).wrapping_mul(0x9e37_79b9_7f4a_7c15);
+        let index = (key >> 50) as usize % PAWN_TABLE_SIZE;
+
+        match self.entries[index] {
+            Some(entry) if entry.white == white && entry.black == black => entry,
+            _ => {
+                let entry = pawn_entry(board, params);
+                self.entries[index] = Some(entry);
+                entry
+            }
+        }
+    }
+}
+
+impl Default for PawnTable {
+    fn default() -> PawnTable {
+        PawnTable::new()
+    }
+}
+
+// What one side has, term by term. Each is a score for that side in
+// centipawns, and they add up to its part of the evaluation.
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Terms {
+    pub material: Score,
+    // the piece-square tables, pawn to king
+    pub squares: [Score; 6],
+    pub pawns: Score,
+    pub mobility: Score,
+    pub king_safety: Score,
+    pub bishop_pair: Score,
+    pub rooks: Score,
+    pub outposts: Score,
+}
+
+impl Terms {
+    // every term with its name, in the order they're printed
+    pub fn rows(&self) -> Vec<(&'static str, Score)> {
+        let [pawn, knight, bishop, rook, queen, king] = self.squares;
+        vec![
+            ("material", self.material),
+            ("pawn squares", pawn),
+            ("knight squares", knight),
+            ("bishop squares", bishop),
+            ("rook squares", rook),
+            ("queen squares", queen),
+            ("king squares", king),
+            ("pawn structure", self.pawns),
+            ("mobility", self.mobility),
+            ("king safety", self.king_safety),
+            ("bishop pair", self.bishop_pair),
+            ("rooks", self.rooks),
+            ("outposts", self.outposts),
+        ]
+    }
+
+    pub fn total(&self) -> Score {
+        // this is every evaluation in the search, so no rows() here
+        let squares = self.squares.iter().fold(Score::default(), |a, &b| a + b);
+        self.material
+            + squares
+            + self.pawns
+            + self.mobility
+            + self.king_safety
+            + self.bishop_pair
+            + self.rooks
+            + self.outposts
+    }
+}
+
+fn side_terms(board: &Board, color: Color, pawn_entry: &PawnEntry, params: &EvalParams) -> Terms {
+    let mut terms = Terms::default();
+    let ours = board.by_color(color);
+    let occupied = board.occupied();
+    let our_pawns = (board.pawns() & ours).0;
+    let their_pawns = (board.pawns() & !ours).0;
+    let defended = pawn_attacks_of(board, color);
+    let chased = pawn_attacks_of(board, !color);
+    let mobility_area = !ours.0 & !chased;
+    let relative_rank = |square: shakmaty::Square| {
+        color.fold(usize::from(square.rank()), 7 - usize::from(square.rank()))
+    };
+
+    let mut bishops = 0;
+    for square in ours {
+        let piece = match board.piece_at(square) {
+            Some(piece) => piece,
+            None => continue,
+        };
+        let role = piece.role as usize - 1;
+        let mg_table = match (piece.role, color) {
+            (Role::Queen, Color::Black) => &params.queen_mg_black,
+            _ => &params.mg_tables[role],
+        };
+        let (value, eg_table) = (params.values[role], &params.eg_tables[role]);
+        let rank = usize::from(square.rank());
+        let row = color.fold(7 - rank, rank);
+        let file = usize::from(square.file());
+        terms.material += Score::new(value, value) * 10;
+        terms.squares[piece.role as usize - 1] +=
+            Score::new(mg_table[row][file], eg_table[row][file]) * 10;
+
+        let kind = match piece.role {
+            Role::Knight => 0,
+            Role::Bishop => 1,
+            Role::Rook => 2,
+            Role::Queen => 3,
+            _ => continue,
+        };
+        let moves = attacks::attacks(square, piece, occupied).0 & mobility_area;
+        let (per_square, first) = (params.mobility[kind], params.mobility_baseline[kind]);
+        terms.mobility += per_square * (moves.count_ones() as i32 - first);
+
+        let bit = 1u64 << usize::from(square);
+        match piece.role {
+            Role::Knight => {
+                let ahead = color.fold(ranks_above(rank), ranks_above(7 - rank).swap_bytes());
+                let (relative, beside) = (relative_rank(square), adjacent_files(file));
+                if (3..=5).contains(&relative)
+                    && defended & bit != 0
+                    && their_pawns & beside & ahead == 0
+                {
+                    terms.outposts += params.knight_outpost;
+                }
+            }
+            Role::Bishop => bishops += 1,
+            Role::Rook => {
+                if (our_pawns | their_pawns) & file_mask(file) == 0 {
+                    terms.rooks += params.rook_open_file;
+                } else if our_pawns & file_mask(file) == 0 {
+                    terms.rooks += params.rook_semi_open_file;
+                }
+                if relative_rank(square) == 6 {
+                    terms.rooks += params.rook_seventh;
+                }
+            }
+            _ => {}
+        }
+    }
+
+    if bishops >= 2 {
+        terms.bishop_pair = params.bishop_pair;
+    }
+
+    // a passed pawn with something in its way is worth half as much
+    terms.pawns = pawn_entry.scores[color.fold(1, 0)];
+    for square in Bitboard(pawn_entry.passed[color.fold(1, 0)]) {
+        let stop = color.fold(usize::from(square) + 8, usize::from(square).wrapping_sub(8));
+        if stop < 64 && occupied.0 & (1 << stop) != 0 {
+            let passed = params.passed[relative_rank(square)];
+            terms.pawns = terms.pawns - Score::new(passed.mg / 2, passed.eg / 2);
+        }
+    }
+
+    terms.king_safety = king_safety(board, color, params);
+    terms
+}
+
+fn king_safety(board: &Board, color: Color, params: &EvalParams) -> Score {
+    let king = match board.king_of(color) {
+        Some(king) => king,
+        None => return Score::default(),
+    };
+    let mut score = Score::default();
+    let our_pawns = (board.pawns() & board.by_color(color)).0;
+    let file = usize::from(king.file());
+    let files = file_mask(file) | adjacent_files(file);
+
+    // the two ranks in front of the king, upside down for black like the
+    // pawn structure
+    let (shield, rank) = color.fold(
+        (our_pawns, usize::from(king.rank())),
+        (our_pawns.swap_bytes(), 7 - usize::from(king.rank())),
+    );
+    let front = ranks_above(rank) & !ranks_above(rank + 2);
+    score += params.pawn_shield * (shield & files & front).count_ones() as i32;
+
+    for f in file.saturating_sub(1)..=(file + 1).min(7) {
+        if our_pawns & file_mask(f) == 0 {
+            score += params.king_open_file;
+        }
+    }
+
+    let zone = attacks::king_attacks(king).0 | 1 << usize::from(king);
+    for square in board.by_color(!color) {
+        let piece = match board.piece_at(square) {
+            Some(piece) => piece,
+            None => continue,
+        };
+        let kind = match piece.role {
+            Role::Knight => 0,
+            Role::Bishop => 1,
+            Role::Rook => 2,
+            Role::Queen => 3,
+            _ => continue,
+        };
+        if attacks::attacks(square, piece, board.occupied()).0 & zone != 0 {
+            score += params.king_attackers[kind];
+        }
+    }
+
+    score
+}
+
+// From white's point of view, a pawn is 10. The middlegame and endgame
+// scores are blended by how much material is left, so nothing jumps when
+// the last queen comes off. `pawns` has to be used with these `params`
+// only.
+pub fn evaluate(board: &Board, params: &EvalParams, pawns: &mut PawnTable) -> i32 {
+    explain_with(board, params, pawns.get(board, params)).score() / 10
+}
+
+// The evaluation of a position taken apart: every term for both sides and
+// the phase they're blended with. Printing it gives a table of them.
+#[derive(Clone, Copy, Debug)]
+pub struct Explanation {
+    pub white: Terms,
+    pub black: Terms,
+    // out of PHASE_TOTAL, all of it is the middlegame
+    pub phase: i32,
+}
+
+impl Explanation {
+    // in centipawns from white's point of view
+    pub fn score(&self) -> i32 {
+        (self.white.total() - self.black.total()).blend(self.phase)
+    }
+}
+
+pub fn explain(board: &Board, params: &EvalParams) -> Explanation {
+    explain_with(board, params, pawn_entry(board, params))
+}
+
+fn explain_with(board: &Board, params: &EvalParams, pawns: PawnEntry) -> Explanation {
+    // promotions can make it more than at the start
+    let phase = board
+        .pieces()
+        .map(|(_, piece)| phase_weight(piece.role))
+        .sum::<i32>()
+        .min(PHASE_TOTAL);
+
+    Explanation {
+        white: side_terms(board, Color::White, &pawns, params),
+        black: side_terms(board, Color::Black, &pawns, params),
+        phase,
+    }
+}
+
+impl fmt::Display for Explanation {
+    // the white and black columns are middlegame/endgame, the last one is
+    // white's edge in the blend of the two
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let row = |f: &mut fmt::Formatter, name: &str, white: Score, black: Score| {
+            writeln!(
+                f,
+                "{:<15} {:>11} {:>11} {:>6}",
+                name,
+                format!("{}/{}", white.mg, white.eg),
+                format!("{}/{}", black.mg, black.eg),
+                (white - black).blend(self.phase)
+            )
+        };
+
+        writeln!(
+            f,
+            "{:<15} {:>11} {:>11} {:>6}",
+            "term", "white", "black", "net"
+        )?;
+        for ((name, white), (_, black)) in self.white.rows().into_iter().zip(self.black.rows()) {
+            row(f, name, white, black)?;
+        }
+        row(f, "total", self.white.total(), self.black.total())?;
+        writeln!(
+            f,
+            "phase {}/{}, {} centipawns for white",
+            self.phase,
+            PHASE_TOTAL,
+            self.score()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::position_from_fen;
+    use shakmaty::{Chess, Setup};
+
+    #[test]
+    fn test_evaluate() {
+        let game = Chess::default();
+        let params = EvalParams::default();
+        let mut table = PawnTable::new();
+        assert_eq!(evaluate(game.board(), &params, &mut table), 0);
+
+        let pawns = |fen: &str| {
+            let board = position_from_fen(fen).unwrap().board().clone();
+            let scores = pawn_entry(&board, &params).scores;
+            scores[1] - scores[0]
+        };
+
+        // doubled and isolated, the front one passed
+        assert_eq!(
+            pawns("4k3/8/8/8/8/4P3/4P3/4K3 w - - 0 1"),
+            Score::new(-20, -40)
+        );
+        // c3 is backward, d4 a passed pawn defended by it, b5 isolated
+        assert_eq!(
+            pawns("4k3/8/8/1p6/3P4/2P5/8/4K3 w - - 0 1"),
+            Score::new(25, 65)
+        );
+
+        let terms = |fen: &str, color: Color| {
+            let game = position_from_fen(fen).unwrap();
+            side_terms(
+                game.board(),
+                color,
+                &pawn_entry(game.board(), &params),
+                &params,
+            )
+        };
+        let knight = terms("4k3/8/8/3N4/2P5/8/8/4K3 w - - 0 1", Color::White);
+        assert_eq!(knight.outposts, params.knight_outpost);
+        let rook = terms("4k3/8/8/8/8/8/8/R3K3 w - - 0 1", Color::White);
+        assert_eq!(rook.rooks, params.rook_open_file);
+        let bishops = terms("4k3/8/8/8/8/8/8/2B1KB2 w - - 0 1", Color::White);
+        assert_eq!(bishops.bishop_pair, params.bishop_pair);
+
+        // castled behind three pawns, on both sides
+        let castled = "6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1";
+        assert_eq!(
+            terms(castled, Color::White).king_safety,
+            params.pawn_shield * 3
+        );
+        assert_eq!(
+            terms(castled, Color::Black).king_safety,
+            params.pawn_shield * 3
+        );
+
+        // the breakdown adds up to the evaluation
+        let game = position_from_fen(castled).unwrap();
+        let explanation = explain(game.board(), &params);
+        assert_eq!(
+            explanation.score() / 10,
+            evaluate(game.board(), &params, &mut table)
+        );
+        assert!(explanation.to_string().contains("king safety"));
+    }
+}
@@ -0,0 +1,183 @@
+extern crate sdl2;
+
+use sdl2::image::{ImageRWops, LoadTexture};
+use sdl2::render::{Texture, TextureCreator};
+use sdl2::rwops::RWops;
+
+use std::env;
+use std::path::{Path, PathBuf};
+
+use crate::error::Error;
+
+// every sprite a piece set needs, without the extension.
+// credits for the default sprites: Wikimedia Commons
+// (https://commons.wikimedia.org/wiki/Category:SVG_chess_pieces)
+pub const SPRITE_NAMES: [&str; 12] = [
+    "b_white", "k_white", "n_white", "p_white", "q_white", "r_white", "b_black", "k_black",
+    "n_black", "p_black", "q_black", "r_black",
+];
+
+// sprites can be PNGs of any size or SVGs, they get scaled to the square size anyway
+const SPRITE_EXTENSIONS: [&str; 2] = ["png", "svg"];
+
+// environment variable pointing at a sprites directory.
+pub const ASSETS_ENV: &str = "CHESS_ASSETS";
+
+// Where the sprites come from: either a directory on disk or the copies
+// compiled into the binary (only with the "portable" feature).
+pub enum Sprites {
+    Dir(PathBuf),
+    Embedded,
+}
+
+impl Sprites {
+    // Looks for a directory holding every sprite, in this order:
+    // the `--assets` path, $CHESS_ASSETS, the XDG data dirs and finally
+    // `sprites/` next to the executable.
+    pub fn find(explicit: Option<&Path>) -> Result<Sprites, Error> {
+        let candidates = candidate_dirs(explicit);
+
+        if let Some(dir) = candidates.iter().find(|dir| has_all_sprites(dir)) {
+            return Ok(Sprites::Dir(dir.to_owned()));
+        }
+
+        // portable builds carry their own copy, so they always have something to show
+        if embedded(SPRITE_NAMES[0]).is_some() {
+            return Ok(Sprites::Embedded);
+        }
+
+        let mut msg = String::from("could not find the chess sprites, tried:");
+        for dir in &candidates {
+            msg.push_str(&format!("\n  {}", dir.display()));
+        }
+        msg.push_str(&format!(
+            "\nuse --assets <dir> or set {} to a directory containing {}.png",
+            ASSETS_ENV, SPRITE_NAMES[0]
+        ));
+
+        Err(Error::Asset {
+            path: None,
+            message: msg,
+        })
+    }
+
+    pub fn load<'a, T>(
+        &self,
+        texture_creator: &'a TextureCreator<T>,
+        name: &str,
+    ) -> Result<Texture<'a>, Error> {
+        match self {
+            Sprites::Dir(dir) => {
+                let path = sprite_path(dir, name)
+                    .ok_or_else(|| Error::asset(dir, format!("no {}.png or {}.svg", name, name)))?;
+                texture_creator
+                    .load_texture(&path)
+                    .map_err(|e| Error::asset(path, e))
+            }
+
+            Sprites::Embedded => {
+                let bytes = embedded(name).ok_or_else(|| Error::Asset {
+                    path: None,
+                    message: format!("no embedded sprite called {}", name),
+                })?;
+                let rwops = RWops::from_bytes(bytes).map_err(Error::Sdl)?;
+                let surface = rwops.load().map_err(Error::Sdl)?;
+                texture_creator
+                    .create_texture_from_surface(surface)
+                    .map_err(|e| Error::Sdl(e.to_string()))
+            }
+        }
+    }
+}
+
+pub fn candidate_dirs(explicit: Option<&Path>) -> Vec<PathBuf> {
+    let mut dirs = Vec::new();
+
+    if let Some(path) = explicit {
+        dirs.push(path.to_owned());
+    }
+
+    if let Some(path) = env::var_os(ASSETS_ENV) {
+        dirs.push(PathBuf::from(path));
+    }
+
+    // https://specifications.freedesktop.org/basedir-spec/latest/
+    match env::var_os("XDG_DATA_HOME") {
+        Some(path) => dirs.push(PathBuf::from(path).join("chess.d/sprites")),
+        None => {
+            if let Some(home) = env::var_os("HOME") {
+                dirs.push(PathBuf::from(home).join(".local/share/chess.d/sprites"));
+            }
+        }
+    }
+
+    let data_dirs = env::var("XDG_DATA_DIRS").unwrap_or_default();
+    let data_dirs = if data_dirs.is_empty() {
+        "/usr/local/share:/usr/share".to_owned()
+    } else {
+        data_dirs
+    };
+    for dir in data_dirs.split(':').filter(|dir| !dir.is_empty()) {
+        dirs.push(Path::new(dir).join("chess.d/sprites"));
+    }
+
+    if let Some(exe_dir) = env::current_exe()
+        .ok()
+        .and_then(|exe| exe.parent().map(Path::to_owned))
+    {
+        dirs.push(exe_dir.join("sprites"));
+    }
+
+    dirs
+}
+
+fn sprite_path(dir: &Path, name: &str) -> Option<PathBuf> {
+    SPRITE_EXTENSIONS
+        .iter()
+        .map(|ext| dir.join(format!("{}.{}", name, ext)))
+        .find(|path| path.is_file())
+}
+
+pub fn has_all_sprites(dir: &Path) -> bool {
+    SPRITE_NAMES
+        .iter()
+        .all(|name| sprite_path(dir, name).is_some())
+}
+
+#[cfg(feature = "portable")]
+fn embedded(name: &str) -> Option<&'static [u8]> {
+    let bytes: &'static [u8] = match name {
+        "b_white" => include_bytes!("../sprites/b_white.png"),
+        "k_white" => include_bytes!("../sprites/k_white.png"),
+        "n_white" => include_bytes!("../sprites/n_white.png"),
+        "p_white" => include_bytes!("../sprites/p_white.png"),
+        "q_white" => include_bytes!("../sprites/q_white.png"),
+        "r_white" => include_bytes!("../sprites/r_white.png"),
+        "b_black" => include_bytes!("../sprites/b_black.png"),
+        "k_black" => include_bytes!("../sprites/k_black.png"),
+        "n_black" => include_bytes!("../sprites/n_black.png"),
+        "p_black" => include_bytes!("../sprites/p_black.png"),
+        "q_black" => include_bytes!("../sprites/q_black.png"),
+        "r_black" => include_bytes!("../sprites/r_black.png"),
+        _ => return None,
+    };
+
+    Some(bytes)
+}
+
+#[cfg(not(feature = "portable"))]
+fn embedded(_name: &str) -> Option<&'static [u8]> {
+    None
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_explicit_dir_comes_first() {
+        let dirs = candidate_dirs(Some(Path::new("my/sprites")));
+        assert_eq!(dirs[0], Path::new("my/sprites"));
+        assert!(dirs.last().unwrap().ends_with("sprites"));
+    }
+}
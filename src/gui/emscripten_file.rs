@@ -0,0 +1,102 @@
+// taken from https://github.com/Gigoteur/PX8/blob/master/src/px8/emscripten.rs
+
+#[cfg(target_os = "emscripten")]
+pub mod emscripten_mod {
+    use std::cell::RefCell;
+    use std::ffi::CString;
+    use std::os::raw::{c_char, c_float, c_int, c_void};
+    use std::ptr::null_mut;
+
+    use crate::controller::{GameController, Input};
+
+    #[allow(non_camel_case_types)]
+    type em_callback_func = unsafe extern "C" fn();
+
+    extern "C" {
+        pub fn emscripten_set_main_loop(
+            func: em_callback_func,
+            fps: c_int,
+            simulate_infinite_loop: c_int,
+        );
+        pub fn emscripten_cancel_main_loop();
+        pub fn emscripten_get_now() -> c_float;
+    }
+
+    thread_local!(static MAIN_LOOP_CALLBACK: RefCell<*mut c_void> = const { RefCell::new(null_mut()) });
+
+    // Never returns: the browser calls `callback` once per frame from now on,
+    // and the stack it borrows from stays where it is.
+    pub fn set_main_loop_callback<F>(callback: F)
+    where
+        F: FnMut(),
+    {
+        MAIN_LOOP_CALLBACK.with(|log| {
+            *log.borrow_mut() = &callback as *const _ as *mut c_void;
+        });
+
+        unsafe {
+            emscripten_set_main_loop(wrapper::<F>, 0, 1);
+        }
+
+        unsafe extern "C" fn wrapper<F>()
+        where
+            F: FnMut(),
+        {
+            MAIN_LOOP_CALLBACK.with(|z| {
+                let closure = *z.borrow_mut() as *mut F;
+                (*closure)();
+            });
+        }
+    }
+
+    pub fn cancel_main_loop() {
+        unsafe {
+            emscripten_cancel_main_loop();
+        }
+    }
+
+    // What the buttons of web/index.html ask for, done by the main loop.
+    enum Request {
+        NewGame,
+        Undo,
+    }
+
+    thread_local!(static REQUESTS: RefCell<Vec<Request>> = const { RefCell::new(Vec::new()) });
+    thread_local!(static PGN: RefCell<CString> = RefCell::new(CString::default()));
+
+    #[no_mangle]
+    pub extern "C" fn chess_new_game() {
+        REQUESTS.with(|requests| requests.borrow_mut().push(Request::NewGame));
+    }
+
+    #[no_mangle]
+    pub extern "C" fn chess_undo() {
+        REQUESTS.with(|requests| requests.borrow_mut().push(Request::Undo));
+    }
+
+    // The game so far as PGN. The page copies the string right away, it only
+    // lives until the next frame.
+    #[no_mangle]
+    pub extern "C" fn chess_pgn() -> *const c_char {
+        PGN.with(|pgn| pgn.borrow().as_ptr())
+    }
+
+    // Once per frame: does what the page asked for and keeps the PGN it
+    // can read up to date.
+    pub fn sync(controller: &mut GameController) {
+        let requests = REQUESTS.with(|requests| requests.replace(Vec::new()));
+        for request in requests {
+            controller.handle(match request {
+                Request::NewGame => Input::NewGame,
+                Request::Undo => Input::Undo,
+            });
+        }
+
+        let pgn = CString::new(controller.pgn()).unwrap_or_default();
+        PGN.with(|current| {
+            if *current.borrow() != pgn {
+                *current.borrow_mut() = pgn;
+            }
+        });
+    }
+}
@@ -0,0 +1,100 @@
+extern crate sdl2;
+extern crate shakmaty;
+
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::Canvas;
+use sdl2::video::Window;
+
+use shakmaty::{Chess, Move};
+
+use crate::gui::font;
+use crate::notation;
+
+const HINT: &str = "Tab: type a move  arrows: move around the board";
+
+// The bar under the board where moves can be typed. It only takes the
+// keyboard while focused, the rest of the time the keys are shortcuts.
+#[derive(Default)]
+pub struct MoveEntry {
+    pub focused: bool,
+    text: String,
+    // the last thing that happened, and whether it went wrong
+    message: Option<(String, bool)>,
+}
+
+impl MoveEntry {
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focused = false;
+        self.text.clear();
+    }
+
+    pub fn insert(&mut self, text: &str) {
+        // the bitmap font only has ASCII
+        self.text
+            .extend(text.chars().filter(|c| c.is_ascii_graphic()));
+    }
+
+    pub fn backspace(&mut self) {
+        self.text.pop();
+    }
+
+    pub fn show(&mut self, message: String) {
+        self.message = Some((message, false));
+    }
+
+    pub fn show_error(&mut self, message: String) {
+        self.message = Some((message, true));
+    }
+
+    // the typed move, if it's one; the error stays on the bar otherwise
+    pub fn submit(&mut self, game: &Chess) -> Option<Move> {
+        match notation::parse_move(game, &self.text) {
+            Ok(m) => {
+                self.text.clear();
+                self.message = None;
+                Some(m)
+            }
+            Err(err) => {
+                self.show_error(err.to_string());
+                None
+            }
+        }
+    }
+
+    pub fn draw(&self, canvas: &mut Canvas<Window>, rect: Rect) {
+        canvas.set_draw_color(Color::RGB(40, 40, 40));
+        let _ = canvas.fill_rect(rect);
+
+        let scale = (rect.height() * 6 / 10 / font::GLYPH_HEIGHT).max(1);
+        let y = rect.y() + (rect.height() as i32 - font::text_height(scale) as i32) / 2;
+        let mut x = rect.x() + font::GLYPH_WIDTH as i32 * scale as i32;
+
+        if self.focused {
+            let prompt = format!("> {}_  ", self.text);
+            canvas.set_draw_color(Color::RGB(230, 230, 230));
+            font::draw_text(canvas, &prompt, x, y, scale);
+            x += font::text_width(&prompt, scale) as i32;
+        }
+
+        match &self.message {
+            Some((message, error)) => {
+                canvas.set_draw_color(if *error {
+                    Color::RGB(255, 90, 90)
+                } else {
+                    Color::RGB(170, 170, 170)
+                });
+                font::draw_text(canvas, message, x, y, scale);
+            }
+            None if !self.focused => {
+                canvas.set_draw_color(Color::RGB(120, 120, 120));
+                font::draw_text(canvas, HINT, x, y, scale);
+            }
+            None => {}
+        }
+    }
+}
@@ -0,0 +1,856 @@
+extern crate sdl2;
+extern crate shakmaty;
+
+use sdl2::event::Event;
+use sdl2::image::InitFlag;
+use sdl2::keyboard::Keycode;
+use sdl2::pixels::Color;
+use sdl2::rect::Rect;
+use sdl2::render::{BlendMode, Canvas, Texture, TextureCreator};
+use sdl2::video::Window;
+use std::{thread, time};
+
+use shakmaty::{Board, Chess, File, Piece, Position, Rank, Role, Setup, Square};
+
+use std::path::Path;
+
+use crate::config::Config;
+use crate::controller::{self, Event as GameEvent, GameController, Input};
+use crate::error::Error;
+use crate::eval;
+use crate::hint::Hint;
+use crate::notation;
+use crate::params::EvalParams;
+use crate::pgn;
+use crate::skill::{self, Skill};
+
+pub mod assets;
+#[cfg(target_os = "emscripten")]
+pub mod emscripten_file;
+pub mod entry;
+pub mod font;
+pub mod sound;
+pub mod theme;
+
+use assets::{Sprites, SPRITE_NAMES};
+use entry::MoveEntry;
+use sound::Sound;
+use theme::{Rgb, Theme};
+
+pub fn init(config: &Config) -> Result<(), Error> {
+    // sdl things
+    let context = sdl2::init().map_err(Error::Sdl)?;
+    let video = context.video().map_err(Error::Sdl)?;
+
+    let _image_context = sdl2::image::init(InitFlag::PNG).map_err(Error::Sdl)?;
+
+    // the board, with the bar to type moves in under it
+    let board_size = config.window_size / 8 * 8;
+    let bar_height = (config.window_size / 16).max(16);
+
+    let window = video
+        .window("Chess", config.window_size, board_size + bar_height)
+        .position_centered()
+        .opengl()
+        .build()
+        .map_err(|e| Error::Sdl(format!("could not create the window: {}", e)))?;
+
+    let mut canvas = window
+        .into_canvas()
+        .software()
+        .build()
+        .map_err(|e| Error::Sdl(e.to_string()))?;
+
+    let mut events = context.event_pump().map_err(Error::Sdl)?;
+
+    let sound = Sound::new(&context, config.sound);
+
+    let mut themes = theme::load_all();
+    let mut theme_index = match &config.theme {
+        Some(name) => select_theme(&mut themes, name)?,
+        None => 0,
+    };
+
+    canvas.set_draw_color(color(themes[theme_index].dark));
+    canvas.clear();
+
+    let texture_creator = canvas.texture_creator();
+
+    // define standard board, or the one we were given
+    let start = match &config.fen {
+        Some(fen) => notation::position_from_fen(fen)?,
+        None => Chess::default(),
+    };
+
+    let (white, black) = config.players();
+    let waiting = |address| println!("waiting for the other player on {}...", address);
+    // for the hints and the evaluation shown, the same as the engine's
+    let params = config.load_eval_params()?;
+    let white = white.create(config, &start, shakmaty::Color::White, waiting)?;
+    let black = black.create(config, &start, shakmaty::Color::Black, waiting)?;
+
+    // the side we root for, and the one at the bottom of the board
+    let human = match (white.is_human(), black.is_human()) {
+        (false, true) => shakmaty::Color::Black,
+        (true, false) => shakmaty::Color::White,
+        _ => config.human,
+    };
+
+    let mut controller = GameController::new(start, white, black);
+    if let Some((secs, increment)) = config.clock {
+        controller = controller.with_clock(
+            time::Duration::from_secs(secs),
+            time::Duration::from_secs(increment),
+        );
+    }
+
+    let sprites = Sprites::find(config.assets.as_deref())?;
+    let mut pieces = PieceSet::load(&themes[theme_index], &sprites, &texture_creator)?;
+
+    // the human's pieces go at the bottom
+    let mut view = View {
+        sqr_size: config.window_size / 8,
+        flipped: human == shakmaty::Color::Black,
+    };
+    let mut show_coordinates = true;
+    // the evaluation, term by term, over the board
+    let mut show_eval = false;
+    // how well the engine plays, changed with + and -
+    let mut skill_level = config.skill;
+    // the move suggested with H, until a move is played
+    let mut hint: Option<Hint> = None;
+
+    // set while a piece is held down with the mouse
+    let mut dragging = false;
+    // the square picked with the arrow keys, shown once they're used
+    let mut cursor: Option<Square> = None;
+    let mut entry = MoveEntry::default();
+    let mut title = String::new();
+
+    // returns false once the player wants to quit
+    let mut main_loop = || {
+        for event in events.poll_iter() {
+            // if esc is pressed, exit main loop
+            // (consequently ending the program)
+            match event {
+                Event::Quit { .. } => return false,
+
+                // while typing a move, the keys go to the bar
+                Event::TextInput { text, .. } if entry.focused => entry.insert(&text),
+                Event::KeyDown {
+                    keycode: Some(key), ..
+                } if entry.focused => match key {
+                    Keycode::Return | Keycode::KpEnter => {
+                        if let Some(m) = entry.submit(controller.position()) {
+                            controller.handle(Input::Play(m));
+                        }
+                    }
+                    Keycode::Backspace => entry.backspace(),
+                    Keycode::Escape | Keycode::Tab => entry.unfocus(),
+                    _ => {}
+                },
+
+                // a web page can't be quit
+                #[cfg(not(target_os = "emscripten"))]
+                Event::KeyDown {
+                    keycode: Some(Keycode::Escape),
+                    ..
+                } => return false,
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::Tab),
+                    ..
+                } => entry.focus(),
+
+                // the keyboard cursor, up being up on the screen
+                Event::KeyDown {
+                    keycode: Some(key @ Keycode::Up),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(key @ Keycode::Down),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(key @ Keycode::Left),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(key @ Keycode::Right),
+                    ..
+                } => {
+                    let (dx, dy) = match key {
+                        Keycode::Up => (0, -1),
+                        Keycode::Down => (0, 1),
+                        Keycode::Left => (-1, 0),
+                        _ => (1, 0),
+                    };
+                    let from = cursor.or(controller.selected()).unwrap_or_else(|| {
+                        view.square_at(board_size as i32 / 2, board_size as i32 * 3 / 4)
+                    });
+                    cursor = Some(view.step(from, dx, dy));
+                }
+
+                // pick up the piece under the cursor, or put down the one we hold
+                Event::KeyDown {
+                    keycode: Some(Keycode::Return),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::KpEnter),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(Keycode::Space),
+                    ..
+                } => {
+                    if let Some(square) = cursor {
+                        let choice = controller
+                            .pending_promotion()
+                            .and_then(|(_, to)| promotion_choice(to, square));
+
+                        match (choice, controller.selected()) {
+                            (Some(role), _) => controller.handle(Input::Promote(role)),
+                            (None, Some(from)) if from != square => {
+                                controller.handle(Input::Drop(square))
+                            }
+                            _ => controller.handle(Input::Select(square)),
+                        }
+                    }
+                }
+
+                // cycle through the themes
+                Event::KeyDown {
+                    keycode: Some(Keycode::T),
+                    ..
+                } => {
+                    theme_index = (theme_index + 1) % themes.len();
+                    match PieceSet::load(&themes[theme_index], &sprites, &texture_creator) {
+                        Ok(new_pieces) => pieces = new_pieces,
+                        Err(err) => {
+                            eprintln!("{}", err);
+                            entry.show_error(err.to_string());
+                        }
+                    }
+                    println!("theme: {}", themes[theme_index].name);
+                }
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::F),
+                    ..
+                } => view.flipped = !view.flipped,
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::C),
+                    ..
+                } => show_coordinates = !show_coordinates,
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::E),
+                    ..
+                } => show_eval = !show_eval,
+
+                Event::KeyDown {
+                    keycode: Some(key @ Keycode::Equals),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(key @ Keycode::Plus),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(key @ Keycode::KpPlus),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(key @ Keycode::Minus),
+                    ..
+                }
+                | Event::KeyDown {
+                    keycode: Some(key @ Keycode::KpMinus),
+                    ..
+                } => {
+                    skill_level = match key {
+                        Keycode::Minus | Keycode::KpMinus => skill_level.saturating_sub(1),
+                        _ => skill_level + 1,
+                    }
+                    .clamp(skill::MIN_LEVEL, skill::MAX_LEVEL);
+
+                    // a human doesn't mind
+                    let skill = Skill::new(skill_level);
+                    for &side in &[shakmaty::Color::White, shakmaty::Color::Black] {
+                        controller.player_mut(side).set_skill(skill.clone());
+                    }
+                    entry.show(if skill.is_full_strength() {
+                        format!("skill level {}, the engine's best", skill.level)
+                    } else {
+                        format!("skill level {}, about {} Elo", skill.level, skill.elo())
+                    });
+                }
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::H),
+                    ..
+                } => {
+                    if controller.hint(&params) {
+                        entry.show("thinking about a hint...".to_owned());
+                    } else {
+                        entry.show_error("hints are for your turn".to_owned());
+                    }
+                }
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::U),
+                    ..
+                } => controller.handle(Input::Undo),
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::N),
+                    ..
+                } => controller.handle(Input::NewGame),
+
+                Event::KeyDown {
+                    keycode: Some(Keycode::R),
+                    ..
+                } => controller.handle(Input::Resign),
+
+                Event::MouseButtonDown { y, .. } if y >= board_size as i32 => entry.focus(),
+
+                Event::MouseButtonDown { x, y, .. } => {
+                    let square = view.square_at(x, y);
+
+                    let choice = controller
+                        .pending_promotion()
+                        .and_then(|(_, to)| promotion_choice(to, square));
+
+                    match choice {
+                        Some(role) => controller.handle(Input::Promote(role)),
+                        None => {
+                            controller.handle(Input::Select(square));
+                            dragging = controller.selected() == Some(square);
+                        }
+                    }
+                }
+
+                Event::MouseButtonUp { x, y, .. } if dragging => {
+                    controller.handle(Input::Drop(view.square_at(x, y)));
+                    dragging = false;
+                }
+                _ => {}
+            }
+        }
+
+        let mut error: Option<Square> = None;
+
+        while let Some(event) = controller.poll_event() {
+            if let GameEvent::Moved { .. } | GameEvent::Undone | GameEvent::NewGame = event {
+                hint = None;
+            }
+
+            match event {
+                GameEvent::Moved { san, by, .. } => {
+                    entry.show(format!("{} played {}", by.fold("white", "black"), san));
+                    sound.play_move();
+                }
+                GameEvent::Illegal(square) => {
+                    entry.show_error("illegal move".to_owned());
+                    error = Some(square);
+                }
+                GameEvent::PlayerError(color, err) => {
+                    eprintln!("{:?}: {}", color, err);
+                    entry.show_error(err);
+                }
+                GameEvent::GameOver(result) => {
+                    entry.show(result.to_string());
+
+                    match result.outcome.winner() {
+                        Some(color) if color == human => println!("You won! Congratulations!!!"),
+                        Some(_) => println!("You lost."),
+                        None => println!("Draw!"),
+                    }
+
+                    if let Some(dir) = &config.autosave {
+                        match pgn::save(dir, &controller.pgn()) {
+                            Ok(path) => println!("game saved to {}", path.display()),
+                            Err(err) => {
+                                eprintln!("could not save the game: {}", err);
+                                entry.show_error(format!("could not save the game: {}", err));
+                            }
+                        }
+                    }
+                }
+                GameEvent::Hint(new_hint) => {
+                    entry.show(format!("hint: {}", new_hint));
+                    hint = Some(new_hint);
+                }
+                GameEvent::PromotionNeeded { .. } | GameEvent::Undone | GameEvent::NewGame => {}
+            }
+        }
+
+        // the clocks go in the title bar, or who the game waits for
+        let new_title = match (
+            controller.waiting_for(),
+            controller.remaining_time(shakmaty::Color::White),
+            controller.remaining_time(shakmaty::Color::Black),
+        ) {
+            (Some(color), _, _) => {
+                format!("Chess - waiting for {}", controller.player(color).name())
+            }
+            (None, Some(white), Some(black)) => format!(
+                "Chess - {} | {}",
+                controller::format_clock(white),
+                controller::format_clock(black)
+            ),
+            _ => "Chess".to_owned(),
+        };
+        if new_title != title {
+            let _ = canvas.window_mut().set_title(&new_title);
+            title = new_title;
+        }
+
+        let game = controller.position();
+        let theme = &themes[theme_index];
+        let mouse_state = events.mouse_state();
+
+        canvas.set_draw_color(color(theme.dark));
+        canvas.clear();
+
+        canvas.set_draw_color(color(theme.light));
+        draw_grid(&mut canvas, &view);
+
+        canvas.set_draw_color(color(theme.highlight));
+        if let Some(m) = controller.last_move() {
+            draw_square(&mut canvas, m.from().unwrap_or_else(|| m.to()), &view);
+            draw_square(&mut canvas, m.to(), &view);
+        }
+        if let Some(square) = controller.selected() {
+            draw_square(&mut canvas, square, &view);
+        }
+
+        canvas.set_draw_color(color(theme.check));
+        draw_check(game, &view, &mut canvas);
+
+        if show_coordinates {
+            draw_coordinates(&mut canvas, theme, &view);
+        }
+
+        // the piece we're dragging follows the mouse instead
+        let held = controller.selected().filter(|_| dragging);
+        draw_pieces(&mut canvas, game.board(), &pieces, &view, held);
+
+        if let Some(piece) = held.and_then(|square| game.board().piece_at(square)) {
+            let size = view.sqr_size as i32;
+            // a sprite that fails to draw isn't worth stopping the game for
+            let _ = canvas.copy(
+                pieces.get(piece),
+                None,
+                Rect::new(
+                    mouse_state.x() - size / 2,
+                    mouse_state.y() - size / 2,
+                    view.sqr_size,
+                    view.sqr_size,
+                ),
+            );
+        }
+
+        if let Some((_, to)) = controller.pending_promotion() {
+            canvas.set_draw_color(color(theme.highlight));
+            draw_promotion_choices(&mut canvas, game.turn(), to, &pieces, &view);
+        }
+
+        if let Some(square) = cursor {
+            canvas.set_draw_color(color(theme.check));
+            draw_cursor(&mut canvas, square, &view);
+        }
+
+        if let Some(hint) = &hint {
+            let (from, to) = hint.squares();
+            draw_arrow(&mut canvas, from, to, &view);
+        }
+
+        if show_eval {
+            draw_eval(&mut canvas, game.board(), &params, &view);
+        }
+
+        entry.draw(
+            &mut canvas,
+            Rect::new(0, board_size as i32, config.window_size, bar_height),
+        );
+
+        if let Some(square) = error {
+            draw_error(square, &view, &mut canvas);
+        }
+
+        canvas.present();
+
+        // the computer thinks here, after we've shown the human's move
+        controller.update();
+
+        // the buttons of the web page
+        #[cfg(target_os = "emscripten")]
+        emscripten_file::emscripten_mod::sync(&mut controller);
+
+        // if you don't do this cpu usage will skyrocket to 100%
+        // (the browser calls us once per frame anyway)
+        #[cfg(not(target_os = "emscripten"))]
+        events.wait_event_timeout(10);
+
+        true
+    };
+
+    #[cfg(target_os = "emscripten")]
+    emscripten_file::emscripten_mod::set_main_loop_callback(move || {
+        if !main_loop() {
+            emscripten_file::emscripten_mod::cancel_main_loop();
+        }
+    });
+
+    #[cfg(not(target_os = "emscripten"))]
+    while main_loop() {}
+
+    Ok(())
+}
+
+//-----------------------------------------------------------------------------------
+
+// When a pawn reaches the last rank the four pieces it can become are shown
+// on its file, from the last rank towards the middle of the board.
+const PROMOTION_ROLES: [Role; 4] = [Role::Queen, Role::Knight, Role::Rook, Role::Bishop];
+
+fn promotion_squares(to: Square) -> Vec<Square> {
+    let step: i32 = if to.rank() == Rank::Eighth { -1 } else { 1 };
+
+    (0..4)
+        .map(|i| {
+            let rank = Rank::new((i32::from(to.rank()) + step * i) as u32);
+            Square::from_coords(to.file(), rank)
+        })
+        .collect()
+}
+
+fn promotion_choice(to: Square, clicked: Square) -> Option<Role> {
+    promotion_squares(to)
+        .iter()
+        .position(|square| *square == clicked)
+        .map(|i| PROMOTION_ROLES[i])
+}
+
+fn draw_promotion_choices(
+    canvas: &mut Canvas<Window>,
+    turn: shakmaty::Color,
+    to: Square,
+    pieces: &PieceSet,
+    view: &View,
+) {
+    for (square, role) in promotion_squares(to)
+        .into_iter()
+        .zip(PROMOTION_ROLES.iter())
+    {
+        draw_square(canvas, square, view);
+        let _ = canvas.copy(
+            pieces.get(Piece {
+                color: turn,
+                role: *role,
+            }),
+            None,
+            view.square_rect(square),
+        );
+    }
+}
+
+// Where the board is on screen: how big the squares are and from which side
+// it's seen. With `flipped` set the board is seen from black's side, h1 at the top left.
+struct View {
+    sqr_size: u32,
+    flipped: bool,
+}
+
+impl View {
+    fn square_at(&self, x: i32, y: i32) -> Square {
+        let col = (x / self.sqr_size as i32).clamp(0, 7) as u32;
+        let row = (y / self.sqr_size as i32).clamp(0, 7) as u32;
+
+        if self.flipped {
+            Square::from_coords(File::new(7 - col), Rank::new(row))
+        } else {
+            Square::from_coords(File::new(col), Rank::new(7 - row))
+        }
+    }
+
+    // the square `dx` columns right and `dy` rows down on the screen, staying on the board
+    fn step(&self, square: Square, dx: i32, dy: i32) -> Square {
+        let rect = self.square_rect(square);
+        let size = self.sqr_size as i32;
+        self.square_at(rect.x() + dx * size, rect.y() + dy * size)
+    }
+
+    fn square_rect(&self, square: Square) -> Rect {
+        let (col, row) = if self.flipped {
+            (7 - u32::from(square.file()), u32::from(square.rank()))
+        } else {
+            (u32::from(square.file()), 7 - u32::from(square.rank()))
+        };
+
+        Rect::new(
+            (col * self.sqr_size) as i32,
+            (row * self.sqr_size) as i32,
+            self.sqr_size,
+            self.sqr_size,
+        )
+    }
+}
+
+// The twelve piece sprites of a theme, in the order of SPRITE_NAMES.
+struct PieceSet<'a> {
+    textures: Vec<Texture<'a>>,
+}
+
+impl<'a> PieceSet<'a> {
+    // uses the theme's own piece set if it has one, otherwise the default sprites
+    fn load<T>(
+        theme: &Theme,
+        default: &Sprites,
+        texture_creator: &'a TextureCreator<T>,
+    ) -> Result<PieceSet<'a>, Error> {
+        let theme_sprites = match &theme.pieces {
+            Some(dir) if assets::has_all_sprites(dir) => Some(Sprites::Dir(dir.to_owned())),
+            Some(dir) => {
+                return Err(Error::asset(
+                    dir,
+                    format!("theme {} is missing some piece sprites", theme.name),
+                ))
+            }
+            None => None,
+        };
+        let sprites = theme_sprites.as_ref().unwrap_or(default);
+
+        let mut textures = Vec::with_capacity(SPRITE_NAMES.len());
+        for name in SPRITE_NAMES.iter() {
+            textures.push(sprites.load(texture_creator, name)?);
+        }
+
+        Ok(PieceSet { textures })
+    }
+
+    fn get(&self, piece: Piece) -> &Texture<'a> {
+        let name = format!(
+            "{}_{}",
+            piece.role.char(),
+            if piece.color == shakmaty::Color::White {
+                "white"
+            } else {
+                "black"
+            }
+        );
+
+        let i = SPRITE_NAMES.iter().position(|n| *n == name).unwrap();
+        &self.textures[i]
+    }
+}
+
+// Picks a theme by name, or loads it if `name` is a theme file.
+fn select_theme(themes: &mut Vec<Theme>, name: &str) -> Result<usize, Error> {
+    if Path::new(name).is_file() {
+        themes.push(Theme::load(Path::new(name))?);
+        return Ok(themes.len() - 1);
+    }
+
+    themes
+        .iter()
+        .position(|theme| theme.name == name)
+        .ok_or_else(|| {
+            let names: Vec<_> = themes.iter().map(|theme| theme.name.as_str()).collect();
+            Error::Config(format!(
+                "unknown theme {}, available: {}",
+                name,
+                names.join(", ")
+            ))
+        })
+}
+
+fn color(rgb: Rgb) -> Color {
+    Color::RGB(rgb.0, rgb.1, rgb.2)
+}
+
+// This will parse and draw all pieces currently on the game to the window.
+// `skip` is left out, it's drawn under the mouse.
+fn draw_pieces(
+    canvas: &mut Canvas<Window>,
+    board: &Board,
+    pieces: &PieceSet,
+    view: &View,
+    skip: Option<Square>,
+) {
+    for (square, piece) in board.pieces().filter(|(square, _)| Some(*square) != skip) {
+        let _ = canvas.copy(pieces.get(piece), None, view.square_rect(square));
+    }
+}
+
+fn draw_square(canvas: &mut Canvas<Window>, square: Square, view: &View) {
+    let _ = canvas.fill_rect(view.square_rect(square));
+}
+
+// a frame around the square, a few pixels thick
+fn draw_cursor(canvas: &mut Canvas<Window>, square: Square, view: &View) {
+    let rect = view.square_rect(square);
+    let thickness = (view.sqr_size / 16).max(2);
+
+    for i in 0..thickness {
+        let _ = canvas.draw_rect(Rect::new(
+            rect.x() + i as i32,
+            rect.y() + i as i32,
+            rect.width() - 2 * i,
+            rect.height() - 2 * i,
+        ));
+    }
+}
+
+// lichess-style coordinates: files along the bottom edge, ranks along the left one,
+// drawn inside the squares in the color of the other kind of square.
+fn draw_coordinates(canvas: &mut Canvas<Window>, theme: &Theme, view: &View) {
+    let scale = (view.sqr_size / 36).max(1);
+    let margin = (view.sqr_size / 20) as i32;
+
+    let bottom_rank = if view.flipped { Rank::Eighth } else { Rank::First };
+    let left_file = if view.flipped { File::H } else { File::A };
+
+    for i in 0..8 {
+        let file = File::new(i);
+        let square = view.square_rect(Square::from_coords(file, bottom_rank));
+        let label = file.char().to_string();
+
+        canvas.set_draw_color(color(label_color(theme, file, bottom_rank)));
+        font::draw_text(
+            canvas,
+            &label,
+            square.right() - margin - font::text_width(&label, scale) as i32,
+            square.bottom() - margin - font::text_height(scale) as i32,
+            scale,
+        );
+
+        let rank = Rank::new(i);
+        let square = view.square_rect(Square::from_coords(left_file, rank));
+
+        canvas.set_draw_color(color(label_color(theme, left_file, rank)));
+        font::draw_text(
+            canvas,
+            &rank.char().to_string(),
+            square.x() + margin,
+            square.y() + margin,
+            scale,
+        );
+    }
+}
+
+// the opposite of the square's own color, a1 being a dark square
+fn label_color(theme: &Theme, file: File, rank: Rank) -> Rgb {
+    if (u32::from(file) + u32::from(rank)) % 2 == 0 {
+        theme.light
+    } else {
+        theme.dark
+    }
+}
+
+// from: https://www.libsdl.org/tmp/SDL/test/testdrawchessboard.c
+fn draw_grid(canvas: &mut Canvas<Window>, view: &View) {
+    let mut row = 0;
+
+    while row < 8 {
+        let mut x = row % 2;
+
+        for _ in (row % 2)..(4 + (row % 2)) {
+            let rect = Rect::new(
+                x * view.sqr_size as i32,
+                row * view.sqr_size as i32,
+                view.sqr_size,
+                view.sqr_size,
+            );
+            x += 2;
+
+            let _ = canvas.fill_rect(rect);
+        }
+
+        row += 1;
+    }
+}
+
+// The table `eval::explain` prints, on a dark background over the board.
+fn draw_eval(canvas: &mut Canvas<Window>, board: &Board, params: &EvalParams, view: &View) {
+    let text = eval::explain(board, params).to_string();
+    let lines: Vec<&str> = text.lines().collect();
+    let board_size = view.sqr_size * 8;
+    let widest = lines
+        .iter()
+        .map(|line| font::text_width(line, 1))
+        .max()
+        .unwrap_or(0);
+    let scale = (board_size * 9 / 10 / widest.max(1)).max(1);
+    let line_height = font::text_height(scale) + 2 * scale;
+
+    let width = widest * scale + 2 * line_height;
+    let height = line_height * (lines.len() as u32 + 1);
+    let x = (board_size as i32 - width as i32) / 2;
+    let y = (board_size as i32 - height as i32) / 2;
+
+    canvas.set_blend_mode(BlendMode::Blend);
+    canvas.set_draw_color(Color::RGBA(0, 0, 0, 200));
+    let _ = canvas.fill_rect(Rect::new(x, y, width, height));
+    canvas.set_blend_mode(BlendMode::None);
+
+    canvas.set_draw_color(Color::RGB(255, 255, 255));
+    for (i, line) in lines.iter().enumerate() {
+        let top = y + (line_height / 2 + i as u32 * line_height) as i32;
+        font::draw_text(canvas, line, x + line_height as i32, top, scale);
+    }
+}
+
+// A thick arrow from the middle of one square to the middle of the other.
+// SDL only draws thin lines, so it's filled with lines side by side.
+fn draw_arrow(canvas: &mut Canvas<Window>, from: Square, to: Square, view: &View) {
+    let start = view.square_rect(from).center();
+    let end = view.square_rect(to).center();
+    let (x0, y0) = (start.x() as f32, start.y() as f32);
+    let (dx, dy) = (end.x() as f32 - x0, end.y() as f32 - y0);
+    let length = (dx * dx + dy * dy).sqrt().max(1.0);
+    // along the arrow and across it
+    let (ux, uy) = (dx / length, dy / length);
+    let (nx, ny) = (-uy, ux);
+
+    let size = view.sqr_size as f32;
+    let (shaft, head_length, head) = (size / 16.0, size * 0.4, size / 4.0);
+    let neck = (x0 + dx - ux * head_length, y0 + dy - uy * head_length);
+    let point = |(x, y): (f32, f32), across: f32| {
+        let x = x + nx * across;
+        let y = y + ny * across;
+        (x.round() as i32, y.round() as i32)
+    };
+
+    canvas.set_draw_color(Color::RGB(21, 120, 27));
+    let mut across = -shaft;
+    while across <= shaft {
+        let _ = canvas.draw_line(point((x0, y0), across), point(neck, across));
+        across += 0.5;
+    }
+    let mut across = -head;
+    while across <= head {
+        let _ = canvas.draw_line(point(neck, across), (end.x(), end.y()));
+        across += 0.5;
+    }
+}
+
+//----------------------------------------------------------------
+// TODO: make this actually work as expected
+
+fn draw_error(square: Square, view: &View, canvas: &mut Canvas<Window>) {
+    canvas.set_draw_color(Color::RGB(255, 5, 5));
+    draw_square(canvas, square, view);
+    thread::sleep(time::Duration::from_millis(100));
+}
+
+fn draw_check(game: &Chess, view: &View, canvas: &mut Canvas<Window>) {
+    if game.is_check() {
+        if let Some(king) = game.board().king_of(game.turn()) {
+            draw_square(canvas, king, view);
+        }
+    }
+}
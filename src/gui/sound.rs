@@ -0,0 +1,61 @@
+extern crate sdl2;
+
+use sdl2::audio::{AudioQueue, AudioSpecDesired};
+use sdl2::Sdl;
+
+const SAMPLE_RATE: i32 = 44100;
+
+// A short wooden "tock" played on every move. Sound is optional, so if
+// there's no audio device we just stay quiet.
+pub struct Sound {
+    queue: Option<AudioQueue<i16>>,
+    tock: Vec<i16>,
+}
+
+impl Sound {
+    pub fn new(context: &Sdl, enabled: bool) -> Sound {
+        let queue = if enabled { open_queue(context) } else { None };
+
+        // 60ms of a quickly decaying 440Hz sine
+        let len = SAMPLE_RATE as usize * 60 / 1000;
+        let tock = (0..len)
+            .map(|i| {
+                let t = i as f32 / SAMPLE_RATE as f32;
+                let decay = (-t * 80.0).exp();
+                ((t * 440.0 * 2.0 * std::f32::consts::PI).sin() * decay * 8000.0) as i16
+            })
+            .collect();
+
+        Sound { queue, tock }
+    }
+
+    pub fn play_move(&self) {
+        if let Some(queue) = &self.queue {
+            queue.clear();
+            queue.queue(&self.tock);
+        }
+    }
+}
+
+fn open_queue(context: &Sdl) -> Option<AudioQueue<i16>> {
+    let spec = AudioSpecDesired {
+        freq: Some(SAMPLE_RATE),
+        channels: Some(1),
+        samples: None,
+    };
+
+    let queue = context
+        .audio()
+        .and_then(|audio| audio.open_queue::<i16, _>(None, &spec));
+
+    match queue {
+        Ok(queue) => {
+            queue.resume();
+            Some(queue)
+        }
+        Err(err) => {
+            eprintln!("no sound: {}", err);
+            None
+        }
+    }
+}
@@ -0,0 +1,184 @@
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::config::{self, parse_kv};
+use crate::error::Error;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    // accepts "#RRGGBB", "RRGGBB" or "r, g, b"
+    pub fn parse(s: &str) -> Result<Rgb, Error> {
+        let s = s.trim();
+        let hex = s.trim_start_matches('#');
+
+        if hex.len() == 6 && hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            if let Ok(rgb) = u32::from_str_radix(hex, 16) {
+                return Ok(Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8));
+            }
+        }
+
+        let channels: Vec<_> = s.split(',').map(|c| c.trim().parse::<u8>()).collect();
+        match channels.as_slice() {
+            [Ok(r), Ok(g), Ok(b)] => Ok(Rgb(*r, *g, *b)),
+            _ => Err(invalid(format!("invalid colour: {}", s))),
+        }
+    }
+}
+
+// What the board looks like. Themes are small text files like this one:
+//
+//     # comments start with '#'
+//     name = green
+//     light = #EEEED2
+//     dark = #769656
+//     highlight = #BACA44
+//     check = #FF0505
+//     pieces = sprites/merida
+//
+// `pieces` is a directory with the twelve piece sprites (`k_white.png`,
+// `q_black.svg`, ...), relative to the theme file. Without it the default
+// sprites are used. Sprites of any size are scaled to the square size.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Theme {
+    pub name: String,
+    pub light: Rgb,
+    pub dark: Rgb,
+    // last move and the square a piece is being dragged from
+    pub highlight: Rgb,
+    pub check: Rgb,
+    pub pieces: Option<PathBuf>,
+}
+
+impl Theme {
+    pub fn builtin() -> Vec<Theme> {
+        let theme = |name: &str, light, dark, highlight| Theme {
+            name: name.to_owned(),
+            light,
+            dark,
+            highlight,
+            check: Rgb(0xFF, 0x05, 0x05),
+            pieces: None,
+        };
+
+        vec![
+            theme(
+                "wood",
+                Rgb(0xFF, 0xCE, 0x9E),
+                Rgb(0xD1, 0x8B, 0x47),
+                Rgb(0xCD, 0xD2, 0x6A),
+            ),
+            theme(
+                "green",
+                Rgb(0xEE, 0xEE, 0xD2),
+                Rgb(0x76, 0x96, 0x56),
+                Rgb(0xBA, 0xCA, 0x44),
+            ),
+            theme(
+                "blue",
+                Rgb(0xDE, 0xE3, 0xE6),
+                Rgb(0x8C, 0xA2, 0xAD),
+                Rgb(0x9B, 0xC7, 0x00),
+            ),
+            theme(
+                "gray",
+                Rgb(0xDC, 0xDC, 0xDC),
+                Rgb(0xAB, 0xAB, 0xAB),
+                Rgb(0xF0, 0xD0, 0x70),
+            ),
+        ]
+    }
+
+    // `dir` is where relative piece-set paths are resolved from.
+    pub fn parse(text: &str, dir: &Path) -> Result<Theme, Error> {
+        // missing keys fall back to the default theme
+        let mut theme = Theme::builtin().remove(0);
+        theme.name = String::new();
+
+        for (key, value) in parse_kv(text)? {
+            match key.as_str() {
+                "name" => theme.name = value,
+                "light" => theme.light = Rgb::parse(&value)?,
+                "dark" => theme.dark = Rgb::parse(&value)?,
+                "highlight" => theme.highlight = Rgb::parse(&value)?,
+                "check" => theme.check = Rgb::parse(&value)?,
+                "pieces" => theme.pieces = Some(dir.join(value)),
+                _ => return Err(invalid(format!("unknown theme key: {}", key))),
+            }
+        }
+
+        if theme.name.is_empty() {
+            return Err(invalid("theme has no name".to_owned()));
+        }
+
+        Ok(theme)
+    }
+
+    pub fn load(path: &Path) -> Result<Theme, Error> {
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path.display(), e))?;
+        let dir = path.parent().unwrap_or_else(|| Path::new("."));
+
+        Theme::parse(&text, dir).map_err(|e| Error::asset(path, e))
+    }
+}
+
+fn invalid(message: String) -> Error {
+    Error::Asset {
+        path: None,
+        message,
+    }
+}
+
+// The built-in themes followed by every `*.theme` file in the user's theme
+// directory. Broken theme files are reported and skipped.
+pub fn load_all() -> Vec<Theme> {
+    let mut themes = Theme::builtin();
+
+    let entries = match themes_dir().map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        _ => return themes,
+    };
+
+    let mut paths: Vec<_> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "theme"))
+        .collect();
+    paths.sort();
+
+    for path in paths {
+        match Theme::load(&path) {
+            Ok(theme) => themes.push(theme),
+            Err(err) => eprintln!("skipping theme {}", err),
+        }
+    }
+
+    themes
+}
+
+// $XDG_CONFIG_HOME/chess/themes, usually ~/.config/chess/themes
+pub fn themes_dir() -> Option<PathBuf> {
+    config::config_dir().map(|dir| dir.join("themes"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_theme() {
+        let theme = Theme::parse(
+            "# mine\nname = mine\nlight = #EEEED2\ndark = 118, 150, 86\npieces = merida\n",
+            Path::new("/themes"),
+        )
+        .unwrap();
+
+        assert_eq!(theme.name, "mine");
+        assert_eq!(theme.light, Rgb(0xEE, 0xEE, 0xD2));
+        assert_eq!(theme.dark, Rgb(118, 150, 86));
+        assert_eq!(theme.check, Rgb(0xFF, 0x05, 0x05));
+        assert_eq!(theme.pieces, Some(PathBuf::from("/themes/merida")));
+
+        assert!(Theme::parse("name = bad\nlight = #GGGGGG", Path::new(".")).is_err());
+    }
+}
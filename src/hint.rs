@@ -0,0 +1,192 @@
+extern crate shakmaty;
+
+use shakmaty::{CastlingSide, Chess, File, Move, Position, Square};
+
+use std::fmt;
+use std::rc::Rc;
+
+use crate::ai;
+use crate::notation;
+use crate::params::EvalParams;
+
+// Short enough not to keep the human waiting.
+pub const DEPTH: u32 = 3;
+
+// The move the engine would play in the human's place, and what it expects
+// to follow.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Hint {
+    pub m: Move,
+    // in centipawns, from the side to move
+    pub score: i32,
+    // moves to checkmate when the line ends in one, negative when it's the
+    // side to move that gets mated
+    pub mate: Option<i32>,
+    // the expected line in SAN, starting with `m`
+    pub pv: Vec<String>,
+}
+
+impl Hint {
+    // where the arrow goes: castling is shown as the king's move
+    pub fn squares(&self) -> (Square, Square) {
+        match (&self.m, self.m.castling_side()) {
+            (Move::Castle { king, .. }, Some(side)) => {
+                let file = match side {
+                    CastlingSide::KingSide => File::G,
+                    CastlingSide::QueenSide => File::C,
+                };
+                (*king, Square::from_coords(file, king.rank()))
+            }
+            (m, _) => (m.from().unwrap_or_else(|| m.to()), m.to()),
+        }
+    }
+}
+
+// "Nf3 (+0.35): Nf3 Nc6 d4", or "Qh5 (#2): Qh5 g6 Qxf7#"
+impl fmt::Display for Hint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.mate {
+            Some(mate) => write!(f, "{} (#{}): ", self.pv[0], mate)?,
+            None => write!(f, "{} ({:+.2}): ", self.pv[0], self.score as f64 / 100.0)?,
+        }
+        write!(f, "{}", self.pv.join(" "))
+    }
+}
+
+// A hint worked out a root move at a time like the engine's search, for
+// front-ends with a frame to draw in between. The line after the best move
+// is found the way minimax scored it: the best answer one ply less deep,
+// and so on.
+pub struct HintSearch {
+    params: Rc<EvalParams>,
+    // the end of the line so far, and the search from there
+    position: Chess,
+    depth: u32,
+    search: ai::RootSearch,
+    // the move and score of the first search
+    best: Option<(Move, i32)>,
+    pv: Vec<String>,
+    done: bool,
+}
+
+impl HintSearch {
+    // searches `game` `depth` plies deep with `params`
+    pub fn new(game: &Chess, depth: u32, params: Rc<EvalParams>) -> HintSearch {
+        HintSearch {
+            search: ai::RootSearch::new(depth, game).with_params(Rc::clone(&params)),
+            params,
+            position: game.clone(),
+            depth,
+            best: None,
+            pv: Vec::new(),
+            done: false,
+        }
+    }
+
+    // searches the next root move, true once the whole line is done
+    pub fn step(&mut self) -> bool {
+        if self.done || !self.search.step() {
+            return self.done;
+        }
+
+        let m = match self.search.result() {
+            Some(result) => {
+                if self.best.is_none() {
+                    self.best = Some((result.best_move.clone(), result.score));
+                }
+                result.best_move
+            }
+            // the line ends in mate or stalemate
+            None => {
+                self.done = true;
+                return true;
+            }
+        };
+        self.pv.push(notation::san(&self.position, &m));
+        self.position.play_unchecked(&m);
+
+        self.depth -= 1;
+        if self.depth == 0 {
+            self.done = true;
+        } else {
+            self.search = ai::RootSearch::new(self.depth, &self.position)
+                .with_params(Rc::clone(&self.params));
+        }
+        self.done
+    }
+
+    // None until it's done, or if there's nothing to play
+    pub fn result(&self) -> Option<Hint> {
+        if !self.done {
+            return None;
+        }
+        let (m, score) = self.best.clone()?;
+
+        // the side to move mates on the odd plies, gets mated on the even ones
+        let plies = self.pv.len() as i32;
+        let mate = if self.position.is_checkmate() {
+            Some(if plies % 2 == 1 {
+                (plies + 1) / 2
+            } else {
+                -plies / 2
+            })
+        } else {
+            None
+        };
+
+        Some(Hint {
+            m,
+            // our pawns are worth 10
+            score: score * 10,
+            mate,
+            pv: self.pv.clone(),
+        })
+    }
+}
+
+// `HintSearch` all at once, None if there's nothing to play.
+pub fn suggest(game: &Chess, depth: u32, params: &Rc<EvalParams>) -> Option<Hint> {
+    let mut search = HintSearch::new(game, depth, Rc::clone(params));
+    while !search.step() {}
+    search.result()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint() {
+        // mate in one, and the arrow of a castling goes to the king's square
+        let game = notation::position_from_fen("6k1/5ppp/8/8/8/8/8/R3K2R w KQ - 0 1").unwrap();
+        let params = Rc::new(EvalParams::default());
+        let hint = suggest(&game, DEPTH, &params).unwrap();
+        assert_eq!(hint.pv, vec!["Ra8#"]);
+        assert!(hint.score > 9000);
+        assert_eq!(hint.mate, Some(1));
+        assert_eq!(hint.to_string(), "Ra8# (#1): Ra8#");
+
+        let castle = notation::parse_move(&game, "O-O").unwrap();
+        let hint = Hint {
+            m: castle,
+            score: 0,
+            mate: None,
+            pv: vec!["O-O".to_owned()],
+        };
+        assert_eq!(hint.squares(), (Square::E1, Square::G1));
+
+        let hint = suggest(&Chess::default(), DEPTH, &params).unwrap();
+        assert_eq!(hint.pv.len(), DEPTH as usize);
+        assert_eq!(hint.squares(), (hint.m.from().unwrap(), hint.m.to()));
+        assert_eq!(hint.mate, None);
+
+        // the other side mates next move whatever we do
+        let lost = notation::position_from_fen("6k1/5ppp/8/8/8/1r5P/r7/6K1 w - - 0 1").unwrap();
+        let hint = suggest(&lost, DEPTH, &params).unwrap();
+        assert_eq!(hint.mate, Some(-1));
+        assert!(hint.to_string().contains("(#-1)"));
+
+        let mated = notation::position_from_fen("R5k1/5ppp/8/8/8/8/8/4K3 b - - 0 1").unwrap();
+        assert!(suggest(&mated, DEPTH, &params).is_none());
+    }
+}
@@ -0,0 +1,27 @@
+// The engine: search and evaluation, notation and game records. None of it
+// needs SDL, the graphical front-end lives in `gui` behind the "sdl" feature.
+extern crate shakmaty;
+
+pub mod ai;
+pub mod bench;
+pub mod book;
+pub mod config;
+pub mod controller;
+pub mod epd;
+pub mod error;
+pub mod eval;
+pub mod hint;
+pub mod notation;
+pub mod params;
+pub mod perft;
+pub mod pgn;
+pub mod player;
+pub mod skill;
+pub mod syzygy;
+pub mod tournament;
+pub mod tui;
+pub mod tune;
+pub mod uci;
+
+#[cfg(feature = "sdl")]
+pub mod gui;
@@ -1,10 +1,43 @@
-pub mod ai;
-pub mod chess;
-pub mod emscripten_file;
+extern crate chess;
+extern crate sdl2;
 
-fn main() -> Result<(), String> {
-    // let's do this!
-    chess::init()?;
+use std::env;
+use std::process;
+
+use sdl2::messagebox::{self, MessageBoxFlag};
+
+use chess::config::{self, Command, Config};
+use chess::error::Error;
+use chess::gui;
+
+fn main() {
+    if let Err(err) = run() {
+        eprintln!("chess: {}", err);
+
+        // started from a desktop there's no terminal to read that in,
+        // but mistakes on the command line were made in one
+        if !matches!(err, Error::Config(_)) {
+            let _ = messagebox::show_simple_message_box(
+                MessageBoxFlag::ERROR,
+                "Chess",
+                &err.to_string(),
+                None,
+            );
+        }
 
-    Ok(())
+        process::exit(err.exit_code());
+    }
+}
+
+fn run() -> Result<(), Error> {
+    let config = match Config::from_args(env::args().skip(1))? {
+        Command::Play(config) => config,
+        Command::Help => {
+            print!("{}", config::HELP);
+            return Ok(());
+        }
+    };
+
+    // let's do this!
+    gui::init(&config)
 }
@@ -0,0 +1,105 @@
+extern crate shakmaty;
+
+use shakmaty::fen::{self, Fen};
+use shakmaty::san::{San, SanPlus};
+use shakmaty::uci::Uci;
+use shakmaty::{Chess, Move, Position};
+
+use crate::error::Error;
+
+// The usual starting position, as the FEN strings we get from users are
+// compared against it.
+pub const STARTING_FEN: &str = "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1";
+
+pub fn position_from_fen(text: &str) -> Result<Chess, Error> {
+    let invalid = |message: String| Error::Fen {
+        fen: text.trim().to_owned(),
+        message,
+    };
+    text.trim()
+        .parse::<Fen>()
+        .map_err(|e| invalid(e.to_string()))?
+        .position::<Chess>()
+        .map_err(|e| invalid(e.to_string()))
+}
+
+pub fn to_fen(game: &Chess) -> String {
+    fen::fen(game)
+}
+
+// `Nf3`, `exd5`, `O-O`, `e8=Q+`...
+pub fn san(game: &Chess, m: &Move) -> String {
+    SanPlus::from_move(game.to_owned(), m).to_string()
+}
+
+// `g1f3`, `e7e8q`, castling as `e1g1`
+pub fn uci(game: &Chess, m: &Move) -> String {
+    Uci::from_move(game, m).to_string()
+}
+
+// Reads a move typed by someone, either in SAN (`Nf3`, `exd5`, `O-O`,
+// `e8=N`) or in long algebraic / UCI notation (`g1f3`, `e7e8n`).
+pub fn parse_move(game: &Chess, text: &str) -> Result<Move, Error> {
+    let text = text.trim();
+
+    if text.is_empty() {
+        return Err(Error::Move("no move given".to_owned()));
+    }
+
+    // long algebraic first: "b1c3" would also be read as a pawn move to c3 otherwise
+    if let Ok(uci) = text.to_ascii_lowercase().parse::<Uci>() {
+        return uci
+            .to_move(game)
+            .map_err(|_| Error::Move(format!("illegal move: {}", text)));
+    }
+
+    // people type castling with zeroes too, and the check and mate suffixes don't matter
+    let san = text
+        .replace('0', "O")
+        .trim_end_matches(['+', '#', '!', '?'])
+        .to_owned();
+
+    let san: San = san
+        .parse()
+        .map_err(|_| Error::Move(format!("not a move: {}", text)))?;
+
+    let legals = game.legals();
+    let candidates: Vec<&Move> = legals.iter().filter(|m| san.matches(m)).collect();
+
+    match candidates.as_slice() {
+        [m] => Ok((*m).clone()),
+        [] => Err(Error::Move(format!("illegal move: {}", text))),
+        _ => {
+            let options: Vec<String> = candidates.iter().map(|m| uci(game, m)).collect();
+            Err(Error::Move(format!(
+                "ambiguous move {}: {}",
+                text,
+                options.join(", ")
+            )))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_move() {
+        let game = Chess::default();
+
+        let nf3 = parse_move(&game, "Nf3").unwrap();
+        assert_eq!(parse_move(&game, "g1f3").unwrap(), nf3);
+        assert_eq!(san(&game, &nf3), "Nf3");
+        assert_eq!(uci(&game, &nf3), "g1f3");
+
+        assert!(parse_move(&game, "Nf4").is_err());
+        assert!(parse_move(&game, "hello").is_err());
+
+        // both knights can go to d2
+        let game = position_from_fen("4k3/8/8/8/8/8/8/1N2KN2 w - - 0 1").unwrap();
+        let err = parse_move(&game, "Nd2").unwrap_err().to_string();
+        assert!(err.contains("b1d2") && err.contains("f1d2"));
+        assert!(parse_move(&game, "Nbd2").is_ok());
+    }
+}
@@ -0,0 +1,484 @@
+use std::fmt;
+use std::fs;
+use std::path::Path;
+
+use crate::config;
+use crate::error::Error;
+use crate::eval::Score;
+
+// Piece-square tables, from white's side with rank 8 at the top (black uses
+// them upside down). There's one for the middlegame and one for the
+// endgame: a king that hides behind its pawns early on should walk to the
+// centre once the heavy pieces are gone, and pawns get worth more the
+// closer they are to promoting.
+const PAWN_MG: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [5, 5, 5, 5, 5, 5, 5, 5],
+    [1, 1, 2, 4, 4, 2, 1, 1],
+    [0, 0, 1, 3, 3, 1, 0, 0],
+    [0, 0, 0, 2, 2, 0, 0, 0],
+    [0, -0, -1, 0, 0, -1, -0, 0],
+    [1, 1, 1, -2, -2, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+];
+
+const PAWN_EG: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [9, 9, 9, 9, 9, 9, 9, 9],
+    [5, 5, 5, 5, 5, 5, 5, 5],
+    [3, 3, 3, 3, 3, 3, 3, 3],
+    [1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+];
+
+const KNIGHT_MG: [[i32; 8]; 8] = [
+    [-5, -4, -3, -3, -3, -3, -4, -5],
+    [-4, -2, 0, 0, 0, 0, -2, -4],
+    [-3, 0, 1, 1, 1, 1, 0, -3],
+    [-3, 1, 2, 2, 2, 1, 0, -3],
+    [-3, 1, 2, 2, 2, 1, 0, -3],
+    [-3, 0, 1, 1, 1, 1, 0, -3],
+    [-4, -2, 0, 0, 0, 0, -2, -4],
+    [-5, -4, -3, -3, -3, -3, -4, -5],
+];
+
+const KNIGHT_EG: [[i32; 8]; 8] = [
+    [-5, -4, -3, -3, -3, -3, -4, -5],
+    [-4, -2, -1, -1, -1, -1, -2, -4],
+    [-3, -1, 1, 1, 1, 1, -1, -3],
+    [-3, -1, 1, 2, 2, 1, -1, -3],
+    [-3, -1, 1, 2, 2, 1, -1, -3],
+    [-3, -1, 1, 1, 1, 1, -1, -3],
+    [-4, -2, -1, -1, -1, -1, -2, -4],
+    [-5, -4, -3, -3, -3, -3, -4, -5],
+];
+
+const BISHOP_MG: [[i32; 8]; 8] = [
+    [-4, -2, -2, -2, -2, -2, -2, -4],
+    [-2, 0, 0, 0, 0, 0, 0, -2],
+    [-2, 0, 1, 2, 2, 2, 0, -2],
+    [-2, 1, 1, 2, 2, 2, 1, -2],
+    [-2, 0, 2, 2, 2, 2, 0, -2],
+    [-2, 2, 2, 2, 2, 2, 2, -2],
+    [-2, 1, 0, 0, 0, 0, 1, -2],
+    [-4, -2, -2, -2, -2, -2, -2, -4],
+];
+
+const BISHOP_EG: [[i32; 8]; 8] = [
+    [-2, -1, -1, -1, -1, -1, -1, -2],
+    [-1, 0, 0, 0, 0, 0, 0, -1],
+    [-1, 0, 1, 1, 1, 1, 0, -1],
+    [-1, 0, 1, 2, 2, 1, 0, -1],
+    [-1, 0, 1, 2, 2, 1, 0, -1],
+    [-1, 0, 1, 1, 1, 1, 0, -1],
+    [-1, 0, 0, 0, 0, 0, 0, -1],
+    [-2, -1, -1, -1, -1, -1, -1, -2],
+];
+
+const ROOK_MG: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [1, 2, 2, 2, 2, 2, 2, 1],
+    [-1, 0, 0, 0, 0, 0, 0, -1],
+    [-1, 0, 0, 0, 0, 0, 0, -1],
+    [-1, 0, 0, 0, 0, 0, 0, -1],
+    [-1, 0, 0, 0, 0, 0, 0, -1],
+    [-1, 0, 0, 0, 0, 0, 0, -1],
+    [0, 0, 0, 1, 1, 0, 0, 0],
+];
+
+const ROOK_EG: [[i32; 8]; 8] = [
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [1, 1, 1, 1, 1, 1, 1, 1],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+    [0, 0, 0, 0, 0, 0, 0, 0],
+];
+
+const QUEEN_MG: [[i32; 8]; 8] = [
+    [-4, -2, -2, -1, -1, -2, -2, -4],
+    [-2, 0, 0, 0, 0, 0, 0, -2],
+    [-2, 0, 1, 1, 1, 1, 0, -2],
+    [-1, 0, 1, 1, 1, 1, 0, -1],
+    [0, 0, 1, 1, 1, 1, 0, -1],
+    [-2, 1, 1, 1, 1, 1, 0, -2],
+    [-2, 0, 1, 0, 0, 0, 0, -2],
+    [-4, -2, -2, -1, -1, -2, -2, -4],
+];
+
+// The old evaluation gave black's queen white's table as it is, where the
+// other pieces had one of their own turned upside down, and the queen
+// table isn't the same both ways up. So black's default is QUEEN_MG
+// upside down, which black then uses upside down again. A file can set it
+// to queen_mg's numbers to treat the two queens alike.
+const QUEEN_MG_BLACK: [[i32; 8]; 8] = [
+    [-4, -2, -2, -1, -1, -2, -2, -4],
+    [-2, 0, 1, 0, 0, 0, 0, -2],
+    [-2, 1, 1, 1, 1, 1, 0, -2],
+    [0, 0, 1, 1, 1, 1, 0, -1],
+    [-1, 0, 1, 1, 1, 1, 0, -1],
+    [-2, 0, 1, 1, 1, 1, 0, -2],
+    [-2, 0, 0, 0, 0, 0, 0, -2],
+    [-4, -2, -2, -1, -1, -2, -2, -4],
+];
+
+const QUEEN_EG: [[i32; 8]; 8] = [
+    [-4, -2, -2, -2, -2, -2, -2, -4],
+    [-2, 0, 0, 0, 0, 0, 0, -2],
+    [-2, 0, 1, 1, 1, 1, 0, -2],
+    [-2, 0, 1, 2, 2, 1, 0, -2],
+    [-2, 0, 1, 2, 2, 1, 0, -2],
+    [-2, 0, 1, 1, 1, 1, 0, -2],
+    [-2, 0, 0, 0, 0, 0, 0, -2],
+    [-4, -2, -2, -2, -2, -2, -2, -4],
+];
+
+const KING_MG: [[i32; 8]; 8] = [
+    [-3, -4, -4, -5, -5, -4, -4, -3],
+    [-3, -4, -4, -5, -5, -4, -4, -3],
+    [-3, -4, -4, -5, -5, -4, -4, -3],
+    [-3, -4, -4, -5, -5, -4, -4, -3],
+    [-2, -3, -3, -4, -4, -3, -3, -2],
+    [-1, -2, -2, -2, -2, -2, -2, -1],
+    [2, 2, 0, 0, 0, 0, 2, 2],
+    [2, 3, 1, 0, 0, 1, 3, 2],
+];
+
+const KING_EG: [[i32; 8]; 8] = [
+    [-5, -4, -3, -2, -2, -3, -4, -5],
+    [-3, -2, -1, 0, 0, -1, -2, -3],
+    [-3, -1, 2, 3, 3, 2, -1, -3],
+    [-3, -1, 3, 4, 4, 3, -1, -3],
+    [-3, -1, 3, 4, 4, 3, -1, -3],
+    [-3, -1, 2, 3, 3, 2, -1, -3],
+    [-3, -3, 0, 0, 0, 0, -3, -3],
+    [-5, -3, -3, -3, -3, -3, -3, -5],
+];
+
+// The terms below are in centipawns, finer than the tables above (where a
+// pawn is 10) so that a square more for a bishop can be worth something.
+
+// pawn structure, per pawn
+const DOUBLED: Score = Score::new(-10, -20);
+const ISOLATED: Score = Score::new(-10, -20);
+// can't be defended by another pawn and can't safely step up either
+const BACKWARD: Score = Score::new(-10, -10);
+// defended by a pawn or side by side with one
+const CONNECTED: Score = Score::new(10, 10);
+// no pawn can stop it anymore, by how far it has come
+const PASSED: [Score; 8] = [
+    Score::new(0, 0),
+    Score::new(0, 10),
+    Score::new(10, 20),
+    Score::new(10, 30),
+    Score::new(20, 50),
+    Score::new(40, 80),
+    Score::new(60, 120),
+    Score::new(0, 0),
+];
+
+// per square a piece can go to, past the first few (the squares our pieces
+// are on and the ones their pawns attack don't count)
+const MOBILITY: [Score; 4] = [
+    // knights, bishops, rooks, queens
+    Score::new(4, 4),
+    Score::new(5, 5),
+    Score::new(2, 4),
+    Score::new(1, 2),
+];
+// how many the first few are
+const MOBILITY_BASELINE: [i32; 4] = [4, 6, 6, 12];
+
+// our pawns in front of the king
+const PAWN_SHIELD: Score = Score::new(12, 0);
+// a file next to the king (or its own) without any of our pawns
+const KING_OPEN_FILE: Score = Score::new(-20, 0);
+// a piece of theirs attacking the squares around the king: knights,
+// bishops, rooks, queens
+const KING_ATTACKERS: [Score; 4] = [
+    Score::new(-10, 0),
+    Score::new(-10, 0),
+    Score::new(-15, 0),
+    Score::new(-25, 0),
+];
+
+const BISHOP_PAIR: Score = Score::new(30, 50);
+// no pawns at all on the file, or none of ours
+const ROOK_OPEN_FILE: Score = Score::new(20, 10);
+const ROOK_SEMI_OPEN_FILE: Score = Score::new(10, 5);
+const ROOK_SEVENTH: Score = Score::new(10, 20);
+// a knight on their half, defended by a pawn, that no pawn of theirs can
+// chase away
+const KNIGHT_OUTPOST: Score = Score::new(20, 10);
+
+// Every weight of the evaluation, so that another set can be tried without
+// rebuilding. The defaults are the ones above, and a file only has to list
+// the ones it changes.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct EvalParams {
+    // pawn to king, a pawn is 10
+    pub values: [i32; 6],
+    // the piece-square tables, pawn to king, in the same units
+    pub mg_tables: [[[i32; 8]; 8]; 6],
+    pub eg_tables: [[[i32; 8]; 8]; 6],
+    // black's middlegame queen table, see QUEEN_MG_BLACK
+    pub queen_mg_black: [[i32; 8]; 8],
+    pub doubled: Score,
+    pub isolated: Score,
+    pub backward: Score,
+    pub connected: Score,
+    pub passed: [Score; 8],
+    // knights, bishops, rooks, queens
+    pub mobility: [Score; 4],
+    pub mobility_baseline: [i32; 4],
+    pub pawn_shield: Score,
+    pub king_open_file: Score,
+    pub king_attackers: [Score; 4],
+    pub bishop_pair: Score,
+    pub rook_open_file: Score,
+    pub rook_semi_open_file: Score,
+    pub rook_seventh: Score,
+    pub knight_outpost: Score,
+}
+
+impl Default for EvalParams {
+    fn default() -> EvalParams {
+        EvalParams {
+            values: [10, 30, 30, 50, 90, 900],
+            mg_tables: [PAWN_MG, KNIGHT_MG, BISHOP_MG, ROOK_MG, QUEEN_MG, KING_MG],
+            eg_tables: [PAWN_EG, KNIGHT_EG, BISHOP_EG, ROOK_EG, QUEEN_EG, KING_EG],
+            queen_mg_black: QUEEN_MG_BLACK,
+            doubled: DOUBLED,
+            isolated: ISOLATED,
+            backward: BACKWARD,
+            connected: CONNECTED,
+            passed: PASSED,
+            mobility: MOBILITY,
+            mobility_baseline: MOBILITY_BASELINE,
+            pawn_shield: PAWN_SHIELD,
+            king_open_file: KING_OPEN_FILE,
+            king_attackers: KING_ATTACKERS,
+            bishop_pair: BISHOP_PAIR,
+            rook_open_file: ROOK_OPEN_FILE,
+            rook_semi_open_file: ROOK_SEMI_OPEN_FILE,
+            rook_seventh: ROOK_SEVENTH,
+            knight_outpost: KNIGHT_OUTPOST,
+        }
+    }
+}
+
+const ROLE_NAMES: [&str; 6] = ["pawn", "knight", "bishop", "rook", "queen", "king"];
+
+fn score(score: &mut Score) -> Vec<&mut i32> {
+    vec![&mut score.mg, &mut score.eg]
+}
+
+fn scores(scores: &mut [Score]) -> Vec<&mut i32> {
+    scores.iter_mut().flat_map(score).collect()
+}
+
+impl EvalParams {
+    // Every parameter by its name in a file, with how many of its numbers
+    // go in a row there (2 for a middlegame and endgame score, 8 for a rank
+    // of a table).
+    fn fields(&mut self) -> Vec<(String, usize, Vec<&mut i32>)> {
+        let mut fields = vec![("values".to_owned(), 6, self.values.iter_mut().collect())];
+
+        let tables = self.mg_tables.iter_mut().zip(self.eg_tables.iter_mut());
+        for (name, (mg, eg)) in ROLE_NAMES.iter().zip(tables) {
+            fields.push((format!("{}_mg", name), 8, mg.iter_mut().flatten().collect()));
+            fields.push((format!("{}_eg", name), 8, eg.iter_mut().flatten().collect()));
+        }
+        fields.push((
+            "queen_mg_black".to_owned(),
+            8,
+            self.queen_mg_black.iter_mut().flatten().collect(),
+        ));
+
+        let terms: Vec<(&str, usize, Vec<&mut i32>)> = vec![
+            ("doubled", 2, score(&mut self.doubled)),
+            ("isolated", 2, score(&mut self.isolated)),
+            ("backward", 2, score(&mut self.backward)),
+            ("connected", 2, score(&mut self.connected)),
+            ("passed", 2, scores(&mut self.passed)),
+            ("mobility", 2, scores(&mut self.mobility)),
+            (
+                "mobility_baseline",
+                4,
+                self.mobility_baseline.iter_mut().collect(),
+            ),
+            ("pawn_shield", 2, score(&mut self.pawn_shield)),
+            ("king_open_file", 2, score(&mut self.king_open_file)),
+            ("king_attackers", 2, scores(&mut self.king_attackers)),
+            ("bishop_pair", 2, score(&mut self.bishop_pair)),
+            ("rook_open_file", 2, score(&mut self.rook_open_file)),
+            (
+                "rook_semi_open_file",
+                2,
+                score(&mut self.rook_semi_open_file),
+            ),
+            ("rook_seventh", 2, score(&mut self.rook_seventh)),
+            ("knight_outpost", 2, score(&mut self.knight_outpost)),
+        ];
+        fields.extend(
+            terms
+                .into_iter()
+                .map(|(name, row, values)| (name.to_owned(), row, values)),
+        );
+
+        fields
+    }
+
+    // Reads `text` over the defaults. It's the config file's syntax, not
+    // TOML: one `name = [numbers]` per line, with a whole table on that one
+    // line however it's bracketed, and '#' comments.
+    pub fn parse(text: &str) -> Result<EvalParams, Error> {
+        let mut params = EvalParams::default();
+
+        for (key, value) in config::parse_kv(text)? {
+            let numbers = value
+                .split(&['[', ']', ','][..])
+                .map(str::trim)
+                .filter(|n| !n.is_empty())
+                .map(|n| n.parse::<i32>())
+                .collect::<Result<Vec<_>, _>>()
+                .map_err(|_| Error::Config(format!("invalid value for {}: {}", key, value)))?;
+
+            let mut fields = params.fields();
+            let slots = match fields.iter_mut().find(|(name, _, _)| *name == key) {
+                Some((_, _, slots)) => slots,
+                None => return Err(Error::Config(format!("unknown parameter: {}", key))),
+            };
+            if numbers.len() != slots.len() {
+                return Err(Error::Config(format!(
+                    "{} needs {} numbers, not {}",
+                    key,
+                    slots.len(),
+                    numbers.len()
+                )));
+            }
+            for (slot, n) in slots.iter_mut().zip(numbers) {
+                **slot = n;
+            }
+        }
+
+        Ok(params)
+    }
+
+    // every number, in the order they're in the file, for the tuner
+    pub fn to_vec(&self) -> Vec<i32> {
+        self.clone()
+            .fields()
+            .into_iter()
+            .flat_map(|(_, _, values)| values.into_iter().map(|value| *value))
+            .collect()
+    }
+
+    pub fn set_vec(&mut self, values: &[i32]) {
+        let slots = self.fields().into_iter().flat_map(|(_, _, slots)| slots);
+        for (slot, value) in slots.zip(values) {
+            *slot = *value;
+        }
+    }
+
+    // what each number of `to_vec` is: the parameter and where in it
+    pub fn names(&self) -> Vec<(String, usize)> {
+        self.clone()
+            .fields()
+            .into_iter()
+            .flat_map(|(name, _, values)| (0..values.len()).map(move |i| (name.clone(), i)))
+            .collect()
+    }
+
+    pub fn load(path: &Path) -> Result<EvalParams, Error> {
+        let text = fs::read_to_string(path).map_err(|e| Error::io(path.display(), e))?;
+        EvalParams::parse(&text).map_err(|e| Error::Config(format!("{}: {}", path.display(), e)))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        fs::write(path, self.to_string()).map_err(|e| Error::io(path.display(), e))
+    }
+}
+
+// the file `load` reads, with every parameter
+impl fmt::Display for EvalParams {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(f, "# chess evaluation parameters")?;
+        writeln!(
+            f,
+            "# pieces and tables: a pawn is 10, the rest in centipawns"
+        )?;
+
+        for (name, row, values) in self.clone().fields() {
+            let rows: Vec<String> = values
+                .chunks(row)
+                .map(|chunk| {
+                    let numbers: Vec<String> = chunk.iter().map(|n| n.to_string()).collect();
+                    format!("[{}]", numbers.join(", "))
+                })
+                .collect();
+
+            if rows.len() == 1 {
+                writeln!(f, "{} = {}", name, rows[0])?;
+            } else {
+                writeln!(f, "{} = [{}]", name, rows.join(", "))?;
+            }
+        }
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::eval::{self, Terms};
+    use crate::notation::position_from_fen;
+    use shakmaty::Setup;
+
+    #[test]
+    fn test_params_round_trip() {
+        let params = EvalParams::default();
+        assert_eq!(EvalParams::parse(&params.to_string()).unwrap(), params);
+
+        let changed = EvalParams::parse("values = [10, 32, 33, 50, 95, 900]\npassed = [[0, 0], [0, 10], [10, 20], [10, 30], [20, 50], [40, 80], [60, 150], [0, 0]]\n").unwrap();
+        assert_eq!(changed.values[2], 33);
+        assert_eq!(changed.passed[6], Score::new(60, 150));
+        assert_eq!(changed.doubled, params.doubled);
+
+        assert!(EvalParams::parse("doubled = [1, 2, 3]").is_err());
+        assert!(EvalParams::parse("tempo = [1, 2]").is_err());
+    }
+
+    #[test]
+    fn test_defaults_match_the_old_evaluation() {
+        // material and squares in the middlegame, worked out with the old
+        // get_values, which counted a pawn as 10 where these are in
+        // centipawns
+        let params = EvalParams::default();
+        let old_score = |fen: &str| {
+            let game = position_from_fen(fen).unwrap();
+            let explanation = eval::explain(game.board(), &params);
+            let side = |terms: &Terms| terms.squares.iter().fold(terms.material, |a, &b| a + b).mg;
+            (side(&explanation.white) - side(&explanation.black)) / 10
+        };
+
+        assert_eq!(
+            old_score("rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1"),
+            0
+        );
+        assert_eq!(
+            old_score("r1b1kbnr/pp1p1ppp/1qn5/4p3/2B1P3/5N2/PPPP1PPP/RNBQK2R w KQkq - 0 1"),
+            14
+        );
+        assert_eq!(old_score("6k1/5ppp/8/8/8/8/5PPP/6K1 w - - 0 1"), 0);
+        // black's queen is where the two ways up of the table differ
+        assert_eq!(old_score("4k3/8/8/q7/8/8/8/4K3 w - - 0 1"), -89);
+        assert_eq!(old_score("4k3/8/8/Q7/8/8/8/4K3 w - - 0 1"), 89);
+        assert_eq!(old_score("4k3/8/8/8/8/1q6/8/4K3 w - - 0 1"), -91);
+    }
+}
@@ -0,0 +1,178 @@
+extern crate shakmaty;
+
+use shakmaty::{Chess, Position};
+
+use crate::error::Error;
+use crate::notation;
+
+// Counts the positions `depth` plies from `game`, to compare with the
+// numbers everyone agrees on (https://www.chessprogramming.org/Perft_Results).
+pub fn perft(game: &Chess, depth: u32) -> u64 {
+    if depth == 0 {
+        return 1;
+    }
+
+    let legals = game.legals();
+    if depth == 1 {
+        return legals.len() as u64;
+    }
+
+    legals
+        .iter()
+        .map(|m| {
+            let mut next = game.clone();
+            next.play_unchecked(m);
+            perft(&next, depth - 1)
+        })
+        .sum()
+}
+
+// `perft` for every move from `game`, in UCI notation. The moves go through
+// the same reading and playing as the ones typed or sent by an engine, so
+// a mistake there shows up as an error instead of a wrong count.
+pub fn divide(game: &Chess, depth: u32) -> Result<Vec<(String, u64)>, Error> {
+    let mut counts = Vec::new();
+
+    for m in game.legals() {
+        let uci = notation::uci(game, &m);
+        let read = notation::parse_move(game, &uci)?;
+        if read != m {
+            return Err(Error::Move(format!("{} reads back as {:?}", uci, read)));
+        }
+
+        let next = game
+            .clone()
+            .play(&read)
+            .map_err(|_| Error::Move(format!("illegal move: {}", uci)))?;
+        counts.push((uci, perft(&next, depth.saturating_sub(1))));
+    }
+
+    counts.sort();
+    Ok(counts)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::controller::{GameController, Input};
+    use crate::player::Human;
+    use shakmaty::{Move, Setup};
+
+    // every move that SAN or UCI notation doesn't give back as itself, `depth`
+    // plies from `game`
+    fn misread_moves(game: &Chess, depth: u32) -> Vec<(String, Move)> {
+        let mut misread = Vec::new();
+        if depth == 0 {
+            return misread;
+        }
+
+        for m in game.legals() {
+            for text in &[notation::uci(game, &m), notation::san(game, &m)] {
+                if notation::parse_move(game, text).ok().as_ref() != Some(&m) {
+                    misread.push((notation::to_fen(game), m.clone()));
+                }
+            }
+
+            let mut next = game.clone();
+            next.play_unchecked(&m);
+            misread.extend(misread_moves(&next, depth - 1));
+        }
+
+        misread
+    }
+
+    // every move that clicking, or dragging, the piece from its square to
+    // where it goes doesn't play the way `play` does, `depth` plies from
+    // `game`
+    fn unclickable_moves(game: &Chess, depth: u32) -> Vec<(String, Move)> {
+        let mut unclickable = Vec::new();
+        if depth == 0 {
+            return unclickable;
+        }
+
+        for m in game.legals() {
+            let from = m.from().unwrap();
+            // castling is clicked as the king going to g1 or c1
+            let to = match m.castling_side() {
+                Some(side) => side.king_to(game.turn()),
+                None => m.to(),
+            };
+
+            let mut next = game.clone();
+            next.play_unchecked(&m);
+
+            for &drag in &[false, true] {
+                let mut controller =
+                    GameController::new(game.clone(), Box::new(Human), Box::new(Human));
+                controller.handle(Input::Select(from));
+                controller.handle(if drag {
+                    Input::Drop(to)
+                } else {
+                    Input::Select(to)
+                });
+                if let Some(role) = m.promotion() {
+                    controller.handle(Input::Promote(role));
+                }
+
+                if notation::to_fen(controller.position()) != notation::to_fen(&next) {
+                    unclickable.push((notation::to_fen(game), m.clone()));
+                }
+            }
+
+            unclickable.extend(unclickable_moves(&next, depth - 1));
+        }
+
+        unclickable
+    }
+
+    // the usual positions, with the counts at each depth
+    // (https://www.chessprogramming.org/Perft_Results)
+    const POSITIONS: [(&str, &[u64]); 6] = [
+        (
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1",
+            &[20, 400, 8902, 197_281],
+        ),
+        // kiwipete
+        (
+            "r3k2r/p1ppqpb1/bn2pnp1/3PN3/1p2P3/2N2Q1p/PPPBBPPP/R3K2R w KQkq - 0 1",
+            &[48, 2039, 97_862],
+        ),
+        (
+            "8/2p5/3p4/KP5r/1R3p1k/8/4P1P1/8 w - - 0 1",
+            &[14, 191, 2812, 43_238],
+        ),
+        (
+            "r3k2r/Pppp1ppp/1b3nbN/nP6/BBP1P3/q4N2/Pp1P2PP/R2Q1RK1 w kq - 0 1",
+            &[6, 264, 9467],
+        ),
+        (
+            "rnbq1k1r/pp1Pbppp/2p5/8/2B5/8/PPP1NnPP/RNBQK2R w KQ - 1 8",
+            &[44, 1486, 62_379],
+        ),
+        (
+            "r4rk1/1pp1qppp/p1np1n2/2b1p1B1/2B1P1b1/P1NP1N2/1PP1QPPP/R4RK1 w - - 0 10",
+            &[46, 2079, 89_890],
+        ),
+    ];
+
+    #[test]
+    fn test_perft() {
+        for (fen, counts) in POSITIONS.iter() {
+            let game = notation::position_from_fen(fen).unwrap();
+            for (depth, count) in counts.iter().enumerate() {
+                assert_eq!(
+                    perft(&game, depth as u32 + 1),
+                    *count,
+                    "{} depth {}",
+                    fen,
+                    depth + 1
+                );
+            }
+
+            let divided = divide(&game, 2).unwrap();
+            assert_eq!(divided.iter().map(|(_, n)| n).sum::<u64>(), counts[1]);
+            assert!(misread_moves(&game, 2).is_empty(), "{}", fen);
+            assert_eq!(unclickable_moves(&game, 2), vec![], "{}", fen);
+        }
+    }
+}
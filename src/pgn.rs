@@ -0,0 +1,113 @@
+extern crate shakmaty;
+
+use shakmaty::{Color, Outcome};
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+// "1-0", "0-1", "1/2-1/2" or "*" for a game still going on
+pub fn result(outcome: Option<Outcome>) -> &'static str {
+    match outcome {
+        Some(Outcome::Decisive { winner: Color::White }) => "1-0",
+        Some(Outcome::Decisive { winner: Color::Black }) => "0-1",
+        Some(Outcome::Draw) => "1/2-1/2",
+        None => "*",
+    }
+}
+
+// Writes a game as PGN. `moves` are in SAN and `fen` is the starting
+// position if the game didn't start from the usual one.
+pub fn write(
+    white: &str,
+    black: &str,
+    fen: Option<&str>,
+    first_move_number: u32,
+    black_starts: bool,
+    moves: &[String],
+    result: &str,
+) -> String {
+    let mut pgn = String::new();
+
+    pgn.push_str("[Event \"Casual game\"]\n");
+    pgn.push_str("[Site \"chess\"]\n");
+    pgn.push_str(&format!("[White \"{}\"]\n", white));
+    pgn.push_str(&format!("[Black \"{}\"]\n", black));
+    pgn.push_str(&format!("[Result \"{}\"]\n", result));
+    if let Some(fen) = fen {
+        pgn.push_str("[SetUp \"1\"]\n");
+        pgn.push_str(&format!("[FEN \"{}\"]\n", fen));
+    }
+    pgn.push('\n');
+
+    let mut line = String::new();
+    let mut number = first_move_number;
+
+    let mut push = |line: &mut String, token: &str| {
+        // PGN lines shouldn't be longer than 80 characters
+        if !line.is_empty() && line.len() + 1 + token.len() > 79 {
+            pgn.push_str(line);
+            pgn.push('\n');
+            line.clear();
+        }
+        if !line.is_empty() {
+            line.push(' ');
+        }
+        line.push_str(token);
+    };
+
+    for (i, san) in moves.iter().enumerate() {
+        let white_to_move = (i % 2 == 0) != black_starts;
+
+        if white_to_move {
+            push(&mut line, &format!("{}.", number));
+        } else if i == 0 {
+            push(&mut line, &format!("{}...", number));
+        }
+
+        push(&mut line, san);
+
+        if !white_to_move {
+            number += 1;
+        }
+    }
+
+    push(&mut line, result);
+    pgn.push_str(&line);
+    pgn.push('\n');
+
+    pgn
+}
+
+// Saves a game in `dir`, named after the time it's saved.
+pub fn save(dir: &Path, pgn: &str) -> io::Result<PathBuf> {
+    fs::create_dir_all(dir)?;
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_secs())
+        .unwrap_or(0);
+    let path = dir.join(format!("chess-{}.pgn", secs));
+
+    fs::write(&path, pgn)?;
+
+    Ok(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_write_numbers_moves() {
+        let moves: Vec<String> = ["e4", "e5", "Nf3"].iter().map(|m| m.to_string()).collect();
+        let pgn = write("Human", "Computer", None, 1, false, &moves, "*");
+        assert!(pgn.ends_with("\n\n1. e4 e5 2. Nf3 *\n"));
+
+        let moves: Vec<String> = ["Kd7", "Kd2"].iter().map(|m| m.to_string()).collect();
+        let pgn = write("Human", "Computer", Some("fen"), 40, true, &moves, "1/2-1/2");
+        assert!(pgn.contains("[FEN \"fen\"]\n"));
+        assert!(pgn.ends_with("\n\n40... Kd7 41. Kd2 1/2-1/2\n"));
+    }
+}
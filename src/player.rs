@@ -0,0 +1,1054 @@
+extern crate shakmaty;
+
+use shakmaty::{Chess, Color, Move, Position};
+
+use std::fmt;
+use std::io::{self, BufRead, BufReader, Read, Write};
+use std::net::{SocketAddr, TcpListener, TcpStream};
+use std::process::{Child, ChildStdin, Command, Stdio};
+use std::rc::Rc;
+use std::sync::mpsc::{self, Receiver, TryRecvError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::ai;
+use crate::book::{Book, Selection};
+use crate::config::Config;
+use crate::controller::GameResult;
+use crate::error::Error;
+use crate::notation;
+use crate::params::EvalParams;
+use crate::skill::{self, Skill};
+use crate::syzygy::Tablebase;
+
+// What a player answers when asked for its move.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Turn {
+    // not decided yet, ask again later
+    Thinking,
+    Move(Move),
+    Resign,
+}
+
+// What an engine found in its last search, to show while it plays.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Thinking {
+    pub depth: u32,
+    // in centipawns, from the engine's side
+    pub score: i32,
+    pub nodes: u64,
+    pub time: Duration,
+    // the moves it expects, in UCI notation
+    pub pv: Vec<String>,
+    // the move came from the opening book, there was no search
+    pub book: bool,
+    // positions found in the endgame tablebases
+    pub tb_hits: u64,
+}
+
+// The game so far, for players that need more than the current position.
+pub struct GameState<'a> {
+    pub start: &'a Chess,
+    pub moves: &'a [Move],
+    pub position: &'a Chess,
+}
+
+// One side of a game. `GameController` asks the player to move with `poll`
+// every frame while it's its turn, so players that wait on something
+// (another process, the network) should answer `Turn::Thinking` rather
+// than block.
+pub trait Player {
+    fn name(&self) -> String;
+
+    // humans move through `GameController::handle` instead of `poll`
+    fn is_human(&self) -> bool {
+        false
+    }
+
+    // False until the player can start, like someone over the network who
+    // hasn't connected or agreed on the game yet. Nobody moves before both
+    // players are ready, and an error ends the game like one from `poll`.
+    fn ready(&mut self) -> Result<bool, Error> {
+        Ok(true)
+    }
+
+    fn poll(&mut self, game: &GameState) -> Result<Turn, Error>;
+
+    // the other side played `m` from `before`
+    fn opponent_moved(&mut self, _before: &Chess, _m: &Move) {}
+
+    // engines tell how their last search went
+    fn thinking(&self) -> Option<Thinking> {
+        None
+    }
+
+    fn game_over(&mut self, _result: GameResult) {}
+
+    // engines that can play below their best play this well from now on
+    fn set_skill(&mut self, _skill: Skill) {}
+
+    // whether moves can be taken back and games restarted with this player,
+    // which a peer on the other end of the network wouldn't know about
+    fn can_take_back(&self) -> bool {
+        true
+    }
+}
+
+// Someone at this computer, playing through the front-end.
+pub struct Human;
+
+impl Player for Human {
+    fn name(&self) -> String {
+        "Human".to_owned()
+    }
+
+    fn is_human(&self) -> bool {
+        true
+    }
+
+    fn poll(&mut self, _game: &GameState) -> Result<Turn, Error> {
+        Ok(Turn::Thinking)
+    }
+}
+
+// The built-in AI. Normally it searches as soon as it's asked, so the
+// front-end stops for as long as that takes. In the browser nothing may
+// block for long, so there it searches a slice at a time and answers
+// `Turn::Thinking` until it's done.
+pub struct Engine {
+    pub depth: u32,
+    pub movetime: Option<Duration>,
+    // how long one `poll` may search, None to search all at once
+    slice: Option<Duration>,
+    search: Option<SlicedSearch>,
+    last_search: Option<Thinking>,
+    book: Option<Book>,
+    // plies into the game after which the book isn't used anymore
+    book_depth: u32,
+    book_selection: Selection,
+    tablebase: Option<Rc<Tablebase>>,
+    params: Rc<EvalParams>,
+    skill: Skill,
+}
+
+// iterative deepening spread over several polls
+struct SlicedSearch {
+    fen: String,
+    search: ai::RootSearch,
+    depth: u32,
+    // of the iterations before this one
+    nodes: u64,
+    tb_hits: u64,
+    started: Instant,
+    iteration_started: Instant,
+}
+
+impl Engine {
+    pub fn new(depth: u32, movetime: Option<Duration>) -> Engine {
+        let slice = if cfg!(target_os = "emscripten") {
+            Some(Duration::from_millis(30))
+        } else {
+            None
+        };
+
+        Engine {
+            depth,
+            movetime,
+            slice,
+            search: None,
+            last_search: None,
+            book: None,
+            book_depth: 0,
+            book_selection: Selection::Weighted,
+            tablebase: None,
+            params: Rc::new(EvalParams::default()),
+            skill: Skill::new(skill::MAX_LEVEL),
+        }
+    }
+
+    // plays from `book` for the first `depth` plies of the game
+    pub fn with_book(mut self, book: Book, depth: u32, selection: Selection) -> Engine {
+        self.book = Some(book);
+        self.book_depth = depth;
+        self.book_selection = selection;
+        self
+    }
+
+    // scores the endgames in `tablebase` from it
+    pub fn with_tablebase(mut self, tablebase: Rc<Tablebase>) -> Engine {
+        self.tablebase = Some(tablebase);
+        self
+    }
+
+    // evaluates with `params` instead of the default ones
+    pub fn with_params(mut self, params: Rc<EvalParams>) -> Engine {
+        self.params = params;
+        self
+    }
+
+    // plays like `skill` instead of its best
+    pub fn with_skill(mut self, skill: Skill) -> Engine {
+        self.skill = skill;
+        self
+    }
+
+    // as deep as it looks, at its skill
+    fn max_depth(&self) -> u32 {
+        self.skill.depth(self.depth)
+    }
+
+    // the book's move, in the first `book_depth` plies of this game however
+    // far into a game it started
+    fn book_move(&mut self, game: &GameState) -> Option<Move> {
+        if game.moves.len() >= self.book_depth as usize {
+            return None;
+        }
+
+        let selection = self.book_selection;
+        self.book.as_mut()?.choose(game.position, selection)
+    }
+
+    // the result and how long it took, once the search is over
+    fn search_slice(
+        &mut self,
+        position: &Chess,
+        slice: Duration,
+    ) -> Result<Option<(ai::SearchResult, Duration)>, Error> {
+        let fen = notation::to_fen(position);
+        let now = Instant::now();
+
+        // a new position, or the game changed while we were thinking
+        if self.search.as_ref().map(|search| &search.fen) != Some(&fen) {
+            // only deepen step by step when there's a time or node limit
+            let limited = self.movetime.is_some() || self.skill.max_nodes().is_some();
+            let depth = if limited { 1 } else { self.max_depth() };
+
+            self.search = Some(SlicedSearch {
+                fen,
+                search: root_search(depth, position, self.tablebase.as_ref(), &self.params),
+                depth,
+                nodes: 0,
+                tb_hits: 0,
+                started: now,
+                iteration_started: now,
+            });
+        }
+
+        let (max_depth, max_nodes) = (self.max_depth(), self.skill.max_nodes());
+        let sliced = self.search.as_mut().unwrap();
+
+        while now.elapsed() < slice {
+            if !sliced.search.step() {
+                continue;
+            }
+
+            let mut result = sliced.search.result().ok_or(Error::NoLegalMoves)?;
+            let last_nodes = result.nodes;
+            result.nodes += sliced.nodes;
+            result.tb_hits += sliced.tb_hits;
+
+            // the same rule as `ai::search_limited`: stop when the next ply won't fit
+            let last_iteration = sliced.iteration_started.elapsed();
+            let out_of_time = self.movetime.is_some_and(|movetime| {
+                sliced.started.elapsed() + last_iteration * 6 > movetime
+            });
+            let out_of_nodes =
+                max_nodes.is_some_and(|max_nodes| result.nodes + last_nodes * 6 > max_nodes);
+
+            if sliced.depth >= max_depth || out_of_time || out_of_nodes {
+                let time = sliced.started.elapsed();
+                self.search = None;
+                return Ok(Some((result, time)));
+            }
+
+            sliced.depth += 1;
+            sliced.nodes = result.nodes;
+            sliced.tb_hits = result.tb_hits;
+            sliced.search = root_search(
+                sliced.depth,
+                position,
+                self.tablebase.as_ref(),
+                &self.params,
+            );
+            sliced.iteration_started = Instant::now();
+        }
+
+        Ok(None)
+    }
+}
+
+fn root_search(
+    depth: u32,
+    position: &Chess,
+    tablebase: Option<&Rc<Tablebase>>,
+    params: &Rc<EvalParams>,
+) -> ai::RootSearch {
+    let search = ai::RootSearch::new(depth, position).with_params(Rc::clone(params));
+    match tablebase {
+        Some(tablebase) => search.with_tablebase(Rc::clone(tablebase)),
+        None => search,
+    }
+}
+
+impl Player for Engine {
+    fn name(&self) -> String {
+        "Computer".to_owned()
+    }
+
+    fn poll(&mut self, game: &GameState) -> Result<Turn, Error> {
+        if let Some(m) = self.book_move(game) {
+            self.last_search = Some(Thinking {
+                pv: vec![notation::uci(game.position, &m)],
+                book: true,
+                ..Thinking::default()
+            });
+            return Ok(Turn::Move(m));
+        }
+
+        let (result, time) = match self.slice {
+            Some(slice) => match self.search_slice(game.position, slice)? {
+                Some(done) => done,
+                None => return Ok(Turn::Thinking),
+            },
+            None => {
+                let start = Instant::now();
+                let result = ai::search_limited(
+                    game.position,
+                    self.max_depth(),
+                    self.movetime,
+                    self.skill.max_nodes(),
+                    self.tablebase.as_ref(),
+                    &self.params,
+                )
+                .ok_or(Error::NoLegalMoves)?;
+                (result, start.elapsed())
+            }
+        };
+
+        // a broken table would go on being skipped without anyone knowing
+        if let Some(err) = self
+            .tablebase
+            .as_ref()
+            .and_then(|tablebase| tablebase.take_error())
+        {
+            return Err(err);
+        }
+
+        let (m, score) = self.skill.pick(&result);
+
+        self.last_search = Some(Thinking {
+            depth: result.depth,
+            // our pawns are worth 10
+            score: score * 10,
+            nodes: result.nodes,
+            time,
+            pv: vec![notation::uci(game.position, &m)],
+            book: false,
+            tb_hits: result.tb_hits,
+        });
+
+        Ok(Turn::Move(m))
+    }
+
+    fn thinking(&self) -> Option<Thinking> {
+        self.last_search.clone()
+    }
+
+    fn set_skill(&mut self, skill: Skill) {
+        self.skill = skill;
+    }
+}
+
+// Any UCI engine (stockfish, another copy of chess-engine...) run as a
+// child process. Its output is read on a thread of its own.
+pub struct UciEngine {
+    name: String,
+    child: Child,
+    stdin: ChildStdin,
+    lines: Receiver<String>,
+    depth: u32,
+    movetime: Option<Duration>,
+    // the `position` command of the search going on, if any
+    searching: Option<String>,
+    // searches we stopped whose bestmove hasn't come in yet
+    stale: usize,
+    last_info: Option<Thinking>,
+}
+
+// how long an engine gets to start up
+const UCI_TIMEOUT: Duration = Duration::from_secs(10);
+
+impl UciEngine {
+    // `command` is split on whitespace, the first word being the program
+    pub fn spawn(
+        command: &str,
+        depth: u32,
+        movetime: Option<Duration>,
+    ) -> Result<UciEngine, Error> {
+        let mut words = command.split_whitespace();
+        let program = words
+            .next()
+            .ok_or_else(|| Error::Config("no engine command given".to_owned()))?;
+
+        let mut child = Command::new(program)
+            .args(words)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::piped())
+            .spawn()
+            .map_err(|e| Error::engine(program, format!("could not run it: {}", e)))?;
+
+        // both were asked for just above, but better an error than a panic
+        let (stdin, stdout) = match (child.stdin.take(), child.stdout.take()) {
+            (Some(stdin), Some(stdout)) => (stdin, stdout),
+            _ => return Err(Error::engine(program, "could not talk to it")),
+        };
+
+        let lines = read_lines(stdout);
+
+        let mut engine = UciEngine {
+            name: program.to_owned(),
+            child,
+            stdin,
+            lines,
+            depth,
+            movetime,
+            searching: None,
+            stale: 0,
+            last_info: None,
+        };
+
+        engine.send("uci")?;
+        while let Some(line) = engine.wait_line()? {
+            if let Some(name) = line.strip_prefix("id name ") {
+                engine.name = name.trim().to_owned();
+            } else if line.trim() == "uciok" {
+                break;
+            }
+        }
+
+        engine.send("isready")?;
+        while engine
+            .wait_line()?
+            .is_some_and(|line| line.trim() != "readyok")
+        {}
+
+        Ok(engine)
+    }
+
+    fn stop(&mut self) -> Result<(), Error> {
+        if self.searching.take().is_some() {
+            self.stale += 1;
+            self.send("stop")?;
+        }
+        Ok(())
+    }
+
+    fn send(&mut self, command: &str) -> Result<(), Error> {
+        writeln!(self.stdin, "{}", command)
+            .and_then(|_| self.stdin.flush())
+            .map_err(|e| Error::engine(&self.name, e))
+    }
+
+    // None once the engine has gone away
+    fn wait_line(&mut self) -> Result<Option<String>, Error> {
+        match self.lines.recv_timeout(UCI_TIMEOUT) {
+            Ok(line) => Ok(Some(line)),
+            Err(mpsc::RecvTimeoutError::Timeout) => {
+                Err(Error::engine(&self.name, "isn't answering"))
+            }
+            Err(mpsc::RecvTimeoutError::Disconnected) => Ok(None),
+        }
+    }
+}
+
+impl Player for UciEngine {
+    fn name(&self) -> String {
+        self.name.clone()
+    }
+
+    fn poll(&mut self, game: &GameState) -> Result<Turn, Error> {
+        // replay the moves rather than sending a fen, so the engine knows about repetitions
+        let mut position = game.start.clone();
+        let mut moves = Vec::with_capacity(game.moves.len());
+        for m in game.moves {
+            moves.push(notation::uci(&position, m));
+            position.play_unchecked(m);
+        }
+
+        let mut command = format!("position fen {}", notation::to_fen(game.start));
+        if !moves.is_empty() {
+            command.push_str(" moves ");
+            command.push_str(&moves.join(" "));
+        }
+
+        // the game changed under the engine, a move was taken back
+        if self
+            .searching
+            .as_ref()
+            .is_some_and(|searching| *searching != command)
+        {
+            self.stop()?;
+        }
+
+        if self.searching.is_none() {
+            self.send(&command)?;
+
+            let go = match self.movetime {
+                Some(movetime) => format!("go movetime {}", movetime.as_millis()),
+                None => format!("go depth {}", self.depth),
+            };
+            self.send(&go)?;
+            self.searching = Some(command);
+        }
+
+        loop {
+            let line = match self.lines.try_recv() {
+                Ok(line) => line,
+                Err(TryRecvError::Empty) => return Ok(Turn::Thinking),
+                Err(TryRecvError::Disconnected) => return Err(Error::engine(&self.name, "quit")),
+            };
+
+            let rest = match line.strip_prefix("bestmove") {
+                Some(rest) => rest,
+                None => {
+                    if let Some(info) = line.strip_prefix("info ").and_then(parse_info) {
+                        if self.stale == 0 {
+                            self.last_info = Some(info);
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            if self.stale > 0 {
+                self.stale -= 1;
+                continue;
+            }
+            self.searching = None;
+
+            return match rest.split_whitespace().next() {
+                // no legal moves, the controller shouldn't have asked
+                None | Some("0000") | Some("(none)") => Ok(Turn::Resign),
+                Some(text) => notation::parse_move(game.position, text)
+                    .map(Turn::Move)
+                    .map_err(|e| Error::engine(&self.name, format!("played {}", e))),
+            };
+        }
+    }
+
+    fn thinking(&self) -> Option<Thinking> {
+        self.last_info.clone()
+    }
+
+    fn game_over(&mut self, _result: GameResult) {
+        let _ = self.stop();
+    }
+}
+
+// `info depth 7 score cp 35 nodes 12345 time 120 pv e2e4 e7e5`, None for
+// the info lines without a score (currmove, string...)
+fn parse_info(info: &str) -> Option<Thinking> {
+    let words: Vec<&str> = info.split_whitespace().collect();
+    let value = |name: &str| {
+        words
+            .iter()
+            .position(|w| *w == name)
+            .and_then(|i| words.get(i + 1))
+    };
+
+    let score = match (value("cp"), value("mate")) {
+        (Some(cp), _) => cp.parse().ok()?,
+        // mate in n, shown as a huge score
+        (None, Some(n)) => n.parse::<i32>().ok()?.signum() * 100_000,
+        (None, None) => return None,
+    };
+
+    Some(Thinking {
+        depth: value("depth").and_then(|v| v.parse().ok()).unwrap_or(0),
+        score,
+        nodes: value("nodes").and_then(|v| v.parse().ok()).unwrap_or(0),
+        time: Duration::from_millis(value("time").and_then(|v| v.parse().ok()).unwrap_or(0)),
+        pv: match words.iter().position(|w| *w == "pv") {
+            Some(i) => words[i + 1..].iter().map(|w| w.to_string()).collect(),
+            None => Vec::new(),
+        },
+        book: false,
+        tb_hits: value("tbhits").and_then(|v| v.parse().ok()).unwrap_or(0),
+    })
+}
+
+impl Drop for UciEngine {
+    fn drop(&mut self) {
+        let _ = self.send("quit");
+        if self.child.try_wait().ok().flatten().is_none() {
+            thread::sleep(Duration::from_millis(100));
+            let _ = self.child.kill();
+        }
+        let _ = self.child.wait();
+    }
+}
+
+// The opponent on another computer, playing through their own copy of the
+// game. Both sides send each other one line per event:
+//
+//     start white rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 1
+//     move e2e4
+//     resign
+//
+// and each one keeps the whole game. `start` comes first, with the side
+// the sender plays and the position the game starts from: the game only
+// begins once both agree on the position and took opposite sides.
+pub struct Remote {
+    peer: String,
+    // the side they play, and the `start` line they should send
+    color: Color,
+    start: String,
+    // None until they connect
+    stream: Option<TcpStream>,
+    lines: Option<Receiver<String>>,
+    // the connection `listen` waits for on another thread
+    incoming: Option<Receiver<io::Result<TcpStream>>>,
+    // they sent a `start` that matches ours
+    agreed: bool,
+    // so their resignation isn't sent back to them
+    resigned: bool,
+}
+
+impl Remote {
+    // Waits for the other side to connect, without blocking: `ready` is
+    // false until they have. `waiting` is called with the address it listens
+    // on, for the front-end to tell the user.
+    pub fn listen<F: FnOnce(SocketAddr)>(
+        port: u16,
+        start: &Chess,
+        color: Color,
+        waiting: F,
+    ) -> Result<Remote, Error> {
+        let place = format!("port {}", port);
+        let listener =
+            TcpListener::bind(("0.0.0.0", port)).map_err(|e| Error::network(&place, e))?;
+        let address = listener
+            .local_addr()
+            .map_err(|e| Error::network(&place, e))?;
+        waiting(address);
+
+        let (sender, incoming) = mpsc::channel();
+        thread::spawn(move || {
+            let _ = sender.send(listener.accept().map(|(stream, _)| stream));
+        });
+
+        let mut remote = Remote::unconnected(format!("port {}", address.port()), start, color);
+        remote.incoming = Some(incoming);
+        Ok(remote)
+    }
+
+    pub fn connect(address: &str, start: &Chess, color: Color) -> Result<Remote, Error> {
+        let stream = TcpStream::connect(address).map_err(|e| Error::network(address, e))?;
+        let mut remote = Remote::unconnected(address.to_owned(), start, color);
+        remote.connected(stream)?;
+        Ok(remote)
+    }
+
+    fn unconnected(peer: String, start: &Chess, color: Color) -> Remote {
+        Remote {
+            peer,
+            color,
+            start: notation::to_fen(start),
+            stream: None,
+            lines: None,
+            incoming: None,
+            agreed: false,
+            resigned: false,
+        }
+    }
+
+    fn connected(&mut self, stream: TcpStream) -> Result<(), Error> {
+        if let Ok(address) = stream.peer_addr() {
+            self.peer = address.to_string();
+        }
+        let reader = stream
+            .try_clone()
+            .map_err(|e| Error::network(&self.peer, e))?;
+        self.lines = Some(read_lines(reader));
+        self.stream = Some(stream);
+
+        let line = format!("start {} {}", color_name(!self.color), self.start);
+        self.send(&line);
+        Ok(())
+    }
+
+    // the next line they sent, None if there's none yet
+    fn next_line(&mut self) -> Result<Option<String>, Error> {
+        let lines = match &self.lines {
+            Some(lines) => lines,
+            None => return Ok(None),
+        };
+        match lines.try_recv() {
+            Ok(line) => Ok(Some(line)),
+            Err(TryRecvError::Empty) => Ok(None),
+            Err(TryRecvError::Disconnected) => Err(Error::network(&self.peer, "left")),
+        }
+    }
+
+    // checks their `start` line once it's there
+    fn agree(&mut self) -> Result<bool, Error> {
+        let line = match self.next_line()? {
+            Some(line) => line,
+            None => return Ok(false),
+        };
+
+        let mut words = line.splitn(3, ' ');
+        let (side, fen) = match (words.next(), words.next(), words.next()) {
+            (Some("start"), Some(side), Some(fen)) => (side, fen.trim()),
+            _ => return Err(Error::network(&self.peer, format!("sent {:?}", line))),
+        };
+        if side != color_name(self.color) {
+            return Err(Error::network(
+                &self.peer,
+                format!("wants to play {}, not {}", side, color_name(self.color)),
+            ));
+        }
+        if fen != self.start {
+            return Err(Error::network(
+                &self.peer,
+                format!("starts from another position: {}", fen),
+            ));
+        }
+
+        self.agreed = true;
+        Ok(true)
+    }
+
+    fn send(&mut self, line: &str) {
+        if let Some(stream) = &mut self.stream {
+            if let Err(err) = writeln!(stream, "{}", line) {
+                eprintln!("{}: {}", self.peer, err);
+            }
+        }
+    }
+}
+
+fn color_name(color: Color) -> &'static str {
+    color.fold("white", "black")
+}
+
+impl Player for Remote {
+    fn name(&self) -> String {
+        self.peer.clone()
+    }
+
+    fn ready(&mut self) -> Result<bool, Error> {
+        if self.agreed {
+            return Ok(true);
+        }
+
+        let accepted = match &self.incoming {
+            Some(incoming) => match incoming.try_recv() {
+                Ok(accepted) => accepted.map_err(|e| Error::network(&self.peer, e))?,
+                Err(TryRecvError::Empty) => return Ok(false),
+                Err(TryRecvError::Disconnected) => {
+                    return Err(Error::network(&self.peer, "stopped listening"))
+                }
+            },
+            None => return self.agree(),
+        };
+        self.incoming = None;
+        self.connected(accepted)?;
+        self.agree()
+    }
+
+    fn poll(&mut self, game: &GameState) -> Result<Turn, Error> {
+        let line = match self.next_line()? {
+            Some(line) => line,
+            None => return Ok(Turn::Thinking),
+        };
+
+        let mut words = line.split_whitespace();
+        match (words.next(), words.next()) {
+            (Some("move"), Some(text)) => notation::parse_move(game.position, text)
+                .map(Turn::Move)
+                .map_err(|e| Error::network(&self.peer, format!("sent {}", e))),
+            (Some("resign"), None) => {
+                self.resigned = true;
+                Ok(Turn::Resign)
+            }
+            _ => Err(Error::network(&self.peer, format!("sent {:?}", line))),
+        }
+    }
+
+    fn opponent_moved(&mut self, before: &Chess, m: &Move) {
+        let line = format!("move {}", notation::uci(before, m));
+        self.send(&line);
+    }
+
+    fn game_over(&mut self, result: GameResult) {
+        if result.reason == crate::controller::EndReason::Resignation && !self.resigned {
+            self.send("resign");
+        }
+    }
+
+    fn can_take_back(&self) -> bool {
+        false
+    }
+}
+
+// Reads `input` line by line on another thread, so the lines can be
+// picked up without waiting for them.
+fn read_lines<R: Read + Send + 'static>(input: R) -> Receiver<String> {
+    let (sender, lines) = mpsc::channel();
+
+    thread::spawn(move || {
+        for line in BufReader::new(input).lines() {
+            let sent = line.map(|line| sender.send(line).is_ok());
+            if sent.ok() != Some(true) {
+                break;
+            }
+        }
+    });
+
+    lines
+}
+
+// Who plays a side, as written in the config file or on the command line:
+// `human`, `engine`, `uci:<command>`, `listen:<port>` or `connect:<host:port>`.
+#[derive(Clone, Debug, PartialEq)]
+pub enum PlayerSpec {
+    Human,
+    Engine,
+    Uci(String),
+    Listen(u16),
+    Connect(String),
+}
+
+impl PlayerSpec {
+    pub fn parse(text: &str) -> Result<PlayerSpec, Error> {
+        let (kind, arg) = match text.find(':') {
+            Some(i) => (&text[..i], Some(text[i + 1..].trim())),
+            None => (text, None),
+        };
+
+        match (kind.trim(), arg) {
+            ("human", None) => Ok(PlayerSpec::Human),
+            ("engine", None) => Ok(PlayerSpec::Engine),
+            ("uci", Some(command)) if !command.is_empty() => {
+                Ok(PlayerSpec::Uci(command.to_owned()))
+            }
+            ("listen", Some(port)) => port
+                .parse()
+                .map(PlayerSpec::Listen)
+                .map_err(|_| Error::Config(format!("invalid port: {}", port))),
+            ("connect", Some(address)) if !address.is_empty() => {
+                Ok(PlayerSpec::Connect(address.to_owned()))
+            }
+            _ => Err(Error::Config(format!("unknown player: {}", text))),
+        }
+    }
+
+    // The player of `color` in a game from `start`. The engines search as
+    // deep and as long as `config` says, and the built-in one plays from its
+    // opening book. `waiting` is called with the address a `listen:<port>`
+    // player waits for the other side on.
+    pub fn create<F: FnOnce(SocketAddr)>(
+        &self,
+        config: &Config,
+        start: &Chess,
+        color: Color,
+        waiting: F,
+    ) -> Result<Box<dyn Player>, Error> {
+        let depth = config.depth;
+        let movetime = config.movetime.map(Duration::from_millis);
+
+        Ok(match self {
+            PlayerSpec::Human => Box::new(Human),
+            PlayerSpec::Engine => {
+                let mut engine = Engine::new(depth, movetime);
+                if let Some(path) = &config.book {
+                    let book = Book::open(path)?;
+                    engine = engine.with_book(book, config.book_depth, config.book_selection);
+                }
+                if let Some(path) = &config.syzygy {
+                    engine = engine.with_tablebase(Rc::new(Tablebase::open(path)?));
+                }
+                engine = engine.with_params(config.load_eval_params()?);
+                engine = engine.with_skill(Skill::new(config.skill));
+                Box::new(engine)
+            }
+            PlayerSpec::Uci(command) => Box::new(UciEngine::spawn(command, depth, movetime)?),
+            PlayerSpec::Listen(port) => Box::new(Remote::listen(*port, start, color, waiting)?),
+            PlayerSpec::Connect(address) => Box::new(Remote::connect(address, start, color)?),
+        })
+    }
+}
+
+impl fmt::Display for PlayerSpec {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            PlayerSpec::Human => write!(f, "human"),
+            PlayerSpec::Engine => write!(f, "engine"),
+            PlayerSpec::Uci(command) => write!(f, "uci:{}", command),
+            PlayerSpec::Listen(port) => write!(f, "listen:{}", port),
+            PlayerSpec::Connect(address) => write!(f, "connect:{}", address),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::book;
+
+    #[test]
+    fn test_player_spec() {
+        for text in &[
+            "human",
+            "engine",
+            "uci:stockfish -x",
+            "listen:7777",
+            "connect:host:7777",
+        ] {
+            assert_eq!(PlayerSpec::parse(text).unwrap().to_string(), *text);
+        }
+        assert!(PlayerSpec::parse("listen:lots").is_err());
+        assert!(PlayerSpec::parse("uci:").is_err());
+    }
+
+    #[test]
+    fn test_sliced_search_finds_the_same_move() {
+        let game = notation::position_from_fen(
+            "r1bqkbnr/pppp1ppp/2n5/4p3/4P3/5N2/PPPP1PPP/RNBQKB1R w KQkq - 2 3",
+        )
+        .unwrap();
+        let state = GameState {
+            start: &game,
+            moves: &[],
+            position: &game,
+        };
+
+        let mut engine = Engine::new(2, None);
+        engine.slice = Some(Duration::from_micros(1));
+
+        let mut polls = 0;
+        let m = loop {
+            polls += 1;
+            match engine.poll(&state).unwrap() {
+                Turn::Thinking => {}
+                Turn::Move(m) => break m,
+                Turn::Resign => panic!("resigned"),
+            }
+        };
+
+        assert!(polls > 1);
+        assert_eq!(m, ai::search(2, &game).unwrap().best_move);
+    }
+
+    #[test]
+    fn test_book_plies_count_from_the_start_of_the_game() {
+        // a game set up halfway through, with e4 and then Nf3 in the book
+        let start = notation::position_from_fen(
+            "rnbqkbnr/pppppppp/8/8/8/8/PPPPPPPP/RNBQKBNR w KQkq - 0 30",
+        )
+        .unwrap();
+        let e4 = notation::parse_move(&start, "e4").unwrap();
+        let after_e4 = start.clone().play(&e4).unwrap();
+        let e5 = notation::parse_move(&after_e4, "e5").unwrap();
+        let after_e5 = after_e4.play(&e5).unwrap();
+
+        // e2e4 and g1f3, sorted by key like a book file
+        let mut entries = vec![
+            (book::key(&start), 12u16 << 6 | 28),
+            (book::key(&after_e5), 6 << 6 | 21),
+        ];
+        entries.sort_unstable();
+        let mut bytes = Vec::new();
+        for (key, m) in entries {
+            bytes.extend_from_slice(&key.to_be_bytes());
+            bytes.extend_from_slice(&m.to_be_bytes());
+            bytes.extend_from_slice(&[0, 1, 0, 0, 0, 0]);
+        }
+        let book = Book::from_bytes(&bytes).unwrap();
+        let mut engine = Engine::new(1, None).with_book(book, 2, Selection::Best);
+
+        let state = GameState {
+            start: &start,
+            moves: &[],
+            position: &start,
+        };
+        assert_eq!(engine.book_move(&state), Some(e4.clone()));
+
+        let moves = [e4, e5];
+        let state = GameState {
+            start: &start,
+            moves: &moves,
+            position: &after_e5,
+        };
+        assert_eq!(engine.book_move(&state), None);
+        engine.book_depth = 3;
+        let m = engine.book_move(&state).unwrap();
+        assert_eq!(notation::san(&after_e5, &m), "Nf3");
+    }
+
+    // both ends of a game over the network, once they've agreed on it or
+    // one of them found they don't
+    fn remotes(
+        host: (&Chess, Color),
+        guest: (&Chess, Color),
+    ) -> (Remote, Remote, Result<(), Error>) {
+        let mut port = 0;
+        let mut host = Remote::listen(0, host.0, host.1, |address| port = address.port()).unwrap();
+        assert!(!host.ready().unwrap());
+        let address = format!("127.0.0.1:{}", port);
+        let mut guest = Remote::connect(&address, guest.0, guest.1).unwrap();
+
+        let agreed = loop {
+            match (host.ready(), guest.ready()) {
+                (Ok(true), Ok(true)) => break Ok(()),
+                (Err(err), _) | (_, Err(err)) => break Err(err),
+                _ => thread::sleep(Duration::from_millis(1)),
+            }
+        };
+        (host, guest, agreed)
+    }
+
+    #[test]
+    fn test_remote_players_exchange_moves() {
+        // the host plays white, so its Remote is black
+        let start = Chess::default();
+        let (mut host, mut guest, agreed) = remotes((&start, Color::Black), (&start, Color::White));
+        agreed.unwrap();
+
+        // the guest's own human played e4, the host sees it come in
+        let e4 = notation::parse_move(&start, "e4").unwrap();
+        guest.opponent_moved(&start, &e4);
+
+        let game = GameState {
+            start: &start,
+            moves: &[],
+            position: &start,
+        };
+        let turn = loop {
+            match host.poll(&game).unwrap() {
+                Turn::Thinking => thread::sleep(Duration::from_millis(1)),
+                turn => break turn,
+            }
+        };
+
+        assert_eq!(turn, Turn::Move(e4));
+    }
+
+    #[test]
+    fn test_remote_players_agree_on_the_game() {
+        let start = Chess::default();
+        let (_, _, agreed) = remotes((&start, Color::Black), (&start, Color::Black));
+        assert!(agreed
+            .unwrap_err()
+            .to_string()
+            .contains("wants to play white"));
+
+        let other = notation::position_from_fen("4k3/8/8/8/8/8/4P3/4K3 w - - 0 1").unwrap();
+        let (_, _, agreed) = remotes((&start, Color::Black), (&other, Color::White));
+        assert!(agreed
+            .unwrap_err()
+            .to_string()
+            .contains("starts from another position"));
+    }
+}
@@ -0,0 +1,163 @@
+extern crate shakmaty;
+
+use shakmaty::Move;
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::ai::SearchResult;
+
+// From a beginner to the engine at its best.
+pub const MIN_LEVEL: u32 = 1;
+pub const MAX_LEVEL: u32 = 20;
+// what the lowest and the highest level are roughly worth, a guess from
+// playing them rather than anything measured
+pub const MIN_ELO: u32 = 600;
+pub const MAX_ELO: u32 = 1800;
+
+// How well the engine plays when it isn't playing its best: the lower the
+// level, the shallower and shorter it searches, and the worse the moves it
+// picks at random instead of the best one. Every now and then it lets
+// itself play a real mistake, the way people do.
+#[derive(Clone, Debug)]
+pub struct Skill {
+    pub level: u32,
+    // state of the random number generator, xorshift like the book's
+    seed: u64,
+}
+
+impl Skill {
+    pub fn new(level: u32) -> Skill {
+        let seed = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|time| time.as_nanos() as u64)
+            .unwrap_or(0);
+
+        Skill {
+            level: level.clamp(MIN_LEVEL, MAX_LEVEL),
+            // xorshift gets stuck on zero
+            seed: seed | 1,
+        }
+    }
+
+    // the level closest to playing at `elo`
+    pub fn from_elo(elo: u32) -> Skill {
+        let elo = elo.clamp(MIN_ELO, MAX_ELO);
+        let steps = MAX_LEVEL - MIN_LEVEL;
+        let above = (elo - MIN_ELO) * steps;
+        let range = MAX_ELO - MIN_ELO;
+        Skill::new(MIN_LEVEL + (above + range / 2) / range)
+    }
+
+    pub fn elo(&self) -> u32 {
+        MIN_ELO + (self.level - MIN_LEVEL) * (MAX_ELO - MIN_ELO) / (MAX_LEVEL - MIN_LEVEL)
+    }
+
+    pub fn is_full_strength(&self) -> bool {
+        self.level >= MAX_LEVEL
+    }
+
+    // the deepest it looks when it could look `depth` plies ahead
+    pub fn depth(&self, depth: u32) -> u32 {
+        match self.level {
+            0..=4 => depth.min(1),
+            5..=9 => depth.min(2),
+            _ => depth,
+        }
+    }
+
+    // how many positions it may search, None for as many as it takes
+    pub fn max_nodes(&self) -> Option<u64> {
+        if self.is_full_strength() {
+            None
+        } else {
+            Some(250 << (self.level / 2))
+        }
+    }
+
+    // The move to play out of the ones `result` searched and its score: the
+    // best one at full strength, otherwise one not much worse, better ones
+    // more often.
+    pub fn pick(&mut self, result: &SearchResult) -> (Move, i32) {
+        let best = (result.best_move.clone(), result.score);
+        if self.is_full_strength() {
+            return best;
+        }
+
+        // how much worse than the best it may be, a pawn is 10
+        let weakness = (MAX_LEVEL - self.level) as i32;
+        let mut margin = weakness;
+        if self.random() % 100 < 2 * weakness as u64 {
+            margin *= 3;
+        }
+
+        let candidates: Vec<(&(Move, i32), u64)> = result
+            .moves
+            .iter()
+            .filter(|(_, score)| result.score - score <= margin)
+            .map(|scored| (scored, (margin - (result.score - scored.1) + 1) as u64))
+            .collect();
+        let total: u64 = candidates.iter().map(|(_, weight)| weight).sum();
+        if total == 0 {
+            return best;
+        }
+
+        let mut pick = self.random() % total;
+        for (scored, weight) in candidates {
+            if pick < weight {
+                return scored.clone();
+            }
+            pick -= weight;
+        }
+        best
+    }
+
+    // xorshift64
+    fn random(&mut self) -> u64 {
+        self.seed ^= self.seed << 13;
+        self.seed ^= self.seed >> 7;
+        self.seed ^= self.seed << 17;
+        self.seed
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::ai;
+    use crate::notation;
+    use shakmaty::Chess;
+
+    #[test]
+    fn test_skill() {
+        assert_eq!(Skill::from_elo(0).level, MIN_LEVEL);
+        assert_eq!(Skill::from_elo(MAX_ELO).level, MAX_LEVEL);
+        for level in MIN_LEVEL..=MAX_LEVEL {
+            assert_eq!(Skill::from_elo(Skill::new(level).elo()).level, level);
+        }
+
+        // plenty of moves are about as good from the start
+        let result = ai::search(2, &Chess::default()).unwrap();
+        let mut strong = Skill::new(MAX_LEVEL);
+        let mut weak = Skill::new(MIN_LEVEL);
+        weak.seed = 12345;
+
+        let mut picked = Vec::new();
+        for _ in 0..100 {
+            assert_eq!(strong.pick(&result).0, result.best_move);
+            let (m, score) = weak.pick(&result);
+            assert!(result.score - score <= 3 * (MAX_LEVEL - MIN_LEVEL) as i32);
+            if !picked.contains(&m) {
+                picked.push(m);
+            }
+        }
+        assert!(picked.len() > 5);
+
+        // but leaving a rook hanging isn't one of them
+        let game = notation::position_from_fen("4k3/8/8/3r4/8/8/3Q4/4K3 w - - 0 1").unwrap();
+        let result = ai::search(2, &game).unwrap();
+        let mut middling = Skill::new(10);
+        for _ in 0..100 {
+            assert_eq!(middling.pick(&result).0, result.best_move);
+        }
+    }
+}
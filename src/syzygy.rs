@@ -0,0 +1,1519 @@
+extern crate shakmaty;
+
+use shakmaty::{Board, Chess, Color, Move, Position, Role, Setup, Square};
+
+use std::cell::{Cell, RefCell};
+use std::collections::HashMap;
+use std::env;
+use std::fs;
+use std::ops::Neg;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use crate::error::Error;
+
+// Probing of Syzygy endgame tablebases, the .rtbw (win/draw/loss) and .rtbz
+// (distance to zeroing the 50-move counter) files. The file format and the
+// way positions are indexed follow Stockfish's tbprobe.cpp, which is where
+// to look for the long version of the comments below.
+
+const MAX_PIECES: usize = 7;
+
+const WDL_MAGIC: [u8; 4] = [0x71, 0xe8, 0x23, 0x5d];
+const DTZ_MAGIC: [u8; 4] = [0xd7, 0x66, 0x0c, 0xa5];
+
+// flags of a table, per side and file
+const STM: u8 = 1;
+const MAPPED: u8 = 2;
+const WIN_PLIES: u8 = 4;
+const LOSS_PLIES: u8 = 8;
+const WIDE: u8 = 16;
+const SINGLE_VALUE: u8 = 128;
+
+// Win, draw or loss for the side to move, with the 50-move counter at zero.
+// A cursed win is a win the 50-move rule turns into a draw, a blessed loss
+// is the same from the other side.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Wdl {
+    Loss,
+    BlessedLoss,
+    Draw,
+    CursedWin,
+    Win,
+}
+
+impl Wdl {
+    fn from_value(value: i32) -> Option<Wdl> {
+        match value {
+            -2 => Some(Wdl::Loss),
+            -1 => Some(Wdl::BlessedLoss),
+            0 => Some(Wdl::Draw),
+            1 => Some(Wdl::CursedWin),
+            2 => Some(Wdl::Win),
+            _ => None,
+        }
+    }
+
+    pub fn signum(self) -> i32 {
+        match self {
+            Wdl::Loss | Wdl::BlessedLoss => -1,
+            Wdl::Draw => 0,
+            Wdl::CursedWin | Wdl::Win => 1,
+        }
+    }
+}
+
+impl Neg for Wdl {
+    type Output = Wdl;
+
+    fn neg(self) -> Wdl {
+        match self {
+            Wdl::Loss => Wdl::Win,
+            Wdl::BlessedLoss => Wdl::CursedWin,
+            Wdl::Draw => Wdl::Draw,
+            Wdl::CursedWin => Wdl::BlessedLoss,
+            Wdl::Win => Wdl::Loss,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+enum Kind {
+    Wdl,
+    Dtz,
+}
+
+impl Kind {
+    fn extension(self) -> &'static str {
+        match self {
+            Kind::Wdl => "rtbw",
+            Kind::Dtz => "rtbz",
+        }
+    }
+}
+
+fn file_of(square: usize) -> usize {
+    square % 8
+}
+
+fn rank_of(square: usize) -> usize {
+    square / 8
+}
+
+// > 0 above the a1-h8 diagonal, < 0 below it
+fn off_diagonal(square: usize) -> i32 {
+    rank_of(square) as i32 - file_of(square) as i32
+}
+
+fn flip_diagonal(square: usize) -> usize {
+    (square >> 3) | ((square & 7) << 3)
+}
+
+// The tables that turn the squares of the pieces into an index, the same
+// for every file.
+struct Indices {
+    map_pawns: [usize; 64],
+    map_b1h1h7: [u64; 64],
+    map_a1d1d4: [usize; 64],
+    map_kk: [[u64; 64]; 10],
+    binomial: [[u64; 64]; MAX_PIECES],
+    lead_pawn_idx: [[u64; 64]; MAX_PIECES],
+    lead_pawns_size: [[u64; 4]; MAX_PIECES],
+}
+
+impl Indices {
+    fn new() -> Indices {
+        let mut ix = Indices {
+            map_pawns: [0; 64],
+            map_b1h1h7: [0; 64],
+            map_a1d1d4: [0; 64],
+            map_kk: [[0; 64]; 10],
+            binomial: [[0; 64]; MAX_PIECES],
+            lead_pawn_idx: [[0; 64]; MAX_PIECES],
+            lead_pawns_size: [[0; 4]; MAX_PIECES],
+        };
+
+        let mut code = 0;
+        for s in 0..64 {
+            if off_diagonal(s) < 0 {
+                ix.map_b1h1h7[s] = code;
+                code += 1;
+            }
+        }
+
+        // the squares below the diagonal in a1-d1-d4 first, then the ones on it
+        let mut code = 0;
+        let mut diagonal = Vec::new();
+        for s in 0..=27 {
+            if off_diagonal(s) < 0 && file_of(s) <= 3 {
+                ix.map_a1d1d4[s] = code;
+                code += 1;
+            } else if off_diagonal(s) == 0 && file_of(s) <= 3 {
+                diagonal.push(s);
+            }
+        }
+        for s in diagonal {
+            ix.map_a1d1d4[s] = code;
+            code += 1;
+        }
+
+        // the 462 ways to put two kings on the board, with the first one in
+        // the a1-d1-d4 triangle
+        let mut code = 0;
+        let mut both_on_diagonal = Vec::new();
+        for idx in 0..10 {
+            for s1 in 0..=27 {
+                if ix.map_a1d1d4[s1] != idx || (idx == 0 && s1 != 1) {
+                    continue;
+                }
+                for s2 in 0..64 {
+                    let touching = (file_of(s1) as i32 - file_of(s2) as i32).abs() <= 1
+                        && (rank_of(s1) as i32 - rank_of(s2) as i32).abs() <= 1;
+                    if touching || (off_diagonal(s1) == 0 && off_diagonal(s2) > 0) {
+                        continue;
+                    }
+                    if off_diagonal(s1) == 0 && off_diagonal(s2) == 0 {
+                        both_on_diagonal.push((idx, s2));
+                    } else {
+                        ix.map_kk[idx][s2] = code;
+                        code += 1;
+                    }
+                }
+            }
+        }
+        for (idx, s2) in both_on_diagonal {
+            ix.map_kk[idx][s2] = code;
+            code += 1;
+        }
+
+        ix.binomial[0][0] = 1;
+        for n in 1..64 {
+            for k in 0..MAX_PIECES.min(n + 1) {
+                ix.binomial[k][n] = if k > 0 { ix.binomial[k - 1][n - 1] } else { 0 }
+                    + if k < n { ix.binomial[k][n - 1] } else { 0 };
+            }
+        }
+
+        // pawns are numbered from the edges in, so the leading pawn (the
+        // one with the highest number) is the one nearest the a file
+        let mut available = 48;
+        for lead in 1..MAX_PIECES {
+            for f in 0..4 {
+                let mut idx = 0;
+                for r in 1..7 {
+                    let s = r * 8 + f;
+                    if lead == 1 {
+                        available -= 1;
+                        ix.map_pawns[s] = available;
+                        available -= 1;
+                        ix.map_pawns[s ^ 7] = available;
+                    }
+                    ix.lead_pawn_idx[lead][s] = idx;
+                    idx += ix.binomial[lead - 1][ix.map_pawns[s]];
+                }
+                ix.lead_pawns_size[lead][f] = idx;
+            }
+        }
+
+        ix
+    }
+}
+
+fn byte(bytes: &[u8], at: usize) -> Option<u8> {
+    bytes.get(at).copied()
+}
+
+fn u16_le(bytes: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes([byte(bytes, at)?, byte(bytes, at + 1)?]))
+}
+
+fn u32_le(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn u32_be(bytes: &[u8], at: usize) -> Option<u32> {
+    let b = bytes.get(at..at + 4)?;
+    Some(u32::from_be_bytes([b[0], b[1], b[2], b[3]]))
+}
+
+fn u64_be(bytes: &[u8], at: usize) -> Option<u64> {
+    let b = bytes.get(at..at + 8)?;
+    let mut buf = [0; 8];
+    buf.copy_from_slice(b);
+    Some(u64::from_be_bytes(buf))
+}
+
+// the two halves of a symbol in the Huffman tree, 12 bits each
+fn pair(bytes: &[u8], btree: usize, sym: usize) -> Option<(usize, usize)> {
+    let lr = bytes.get(btree + 3 * sym..btree + 3 * sym + 3)?;
+    let left = (usize::from(lr[1] & 0xf) << 8) | usize::from(lr[0]);
+    let right = (usize::from(lr[2]) << 4) | usize::from(lr[1] >> 4);
+    Some((left, right))
+}
+
+// How one side (and one file, with pawns) of a table is encoded. The
+// offsets are into the whole file.
+#[derive(Default)]
+struct PairsData {
+    flags: u8,
+    min_sym_len: u8,
+    block_size: u64,
+    span: u64,
+    num_blocks: u64,
+    block_length_size: u64,
+    sparse_index_size: u64,
+    lowest_sym: usize,
+    btree: usize,
+    sparse_index: usize,
+    block_length: usize,
+    data: usize,
+    base64: Vec<u64>,
+    symlen: Vec<u32>,
+    pieces: [u8; MAX_PIECES],
+    group_idx: [u64; MAX_PIECES + 1],
+    group_len: [usize; MAX_PIECES + 1],
+    // where the DTZ values of wins, losses, cursed wins and blessed losses
+    // are remapped
+    map_idx: [usize; 4],
+}
+
+struct Table {
+    kind: Kind,
+    bytes: Vec<u8>,
+    // the material as in the file name, and with the colors swapped, see
+    // `material_key`
+    key: u64,
+    key2: u64,
+    piece_count: usize,
+    has_pawns: bool,
+    has_unique_pieces: bool,
+    // pawns of the side with the leading pawn, then of the other one
+    pawn_count: [usize; 2],
+    items: [[PairsData; 4]; 2],
+}
+
+// None for anything that isn't a table we can read, never a panic: the
+// files come from the user.
+impl Table {
+    fn new(name: &str, kind: Kind, bytes: Vec<u8>, ix: &Indices) -> Option<Table> {
+        let magic = match kind {
+            Kind::Wdl => WDL_MAGIC,
+            Kind::Dtz => DTZ_MAGIC,
+        };
+        if bytes.get(..4)? != magic {
+            return None;
+        }
+
+        let mut sides = name.splitn(2, 'v');
+        let (white, black) = (sides.next()?, sides.next()?);
+        let count = |side: &str, c: char| side.chars().filter(|&x| x == c).count();
+        let (white_pawns, black_pawns) = (count(white, 'P'), count(black, 'P'));
+        let lead_white = black_pawns == 0 || (white_pawns > 0 && black_pawns >= white_pawns);
+
+        let mut table = Table {
+            kind,
+            bytes,
+            key: name_key(name)?,
+            key2: name_key(&format!("{}v{}", black, white))?,
+            piece_count: white.len() + black.len(),
+            has_pawns: white_pawns + black_pawns > 0,
+            has_unique_pieces: [white, black]
+                .iter()
+                .any(|side| "QRBNP".chars().any(|c| count(side, c) == 1)),
+            pawn_count: if lead_white {
+                [white_pawns, black_pawns]
+            } else {
+                [black_pawns, white_pawns]
+            },
+            items: Default::default(),
+        };
+        if table.piece_count > MAX_PIECES {
+            return None;
+        }
+
+        table.items = table.parse(ix)?;
+        Some(table)
+    }
+
+    fn parse(&self, ix: &Indices) -> Option<[[PairsData; 4]; 2]> {
+        let bytes = &self.bytes;
+        let mut items: [[PairsData; 4]; 2] = Default::default();
+
+        let flags = byte(bytes, 4)?;
+        if (flags & 2 != 0) != self.has_pawns || (flags & 1 != 0) != (self.key != self.key2) {
+            return None;
+        }
+        let mut at = 5;
+
+        let sides = if self.kind == Kind::Wdl && self.key != self.key2 {
+            2
+        } else {
+            1
+        };
+        let files = if self.has_pawns { 4 } else { 1 };
+        // are there pawns on both sides
+        let pp = self.has_pawns && self.pawn_count[1] > 0;
+
+        for f in 0..files {
+            let b0 = byte(bytes, at)?;
+            let b1 = if pp { byte(bytes, at + 1)? } else { 0xff };
+            let order = [[b0 & 0xf, b1 & 0xf], [b0 >> 4, b1 >> 4]];
+            at += 1 + pp as usize;
+
+            for k in 0..self.piece_count {
+                let b = byte(bytes, at)?;
+                items[0][f].pieces[k] = b & 0xf;
+                items[1][f].pieces[k] = b >> 4;
+                at += 1;
+            }
+            for (side, order) in items.iter_mut().zip(order.iter()).take(sides) {
+                self.set_groups(&mut side[f], *order, f, ix)?;
+            }
+        }
+        at += at & 1;
+
+        for f in 0..files {
+            for side in items.iter_mut().take(sides) {
+                at = set_sizes(&mut side[f], bytes, at)?;
+            }
+        }
+
+        if self.kind == Kind::Dtz {
+            for d in items[0].iter_mut().take(files) {
+                if d.flags & MAPPED == 0 {
+                    continue;
+                }
+                if d.flags & WIDE != 0 {
+                    at += at & 1;
+                    for i in 0..4 {
+                        d.map_idx[i] = at + 2;
+                        at += 2 * usize::from(u16_le(bytes, at)?) + 2;
+                    }
+                } else {
+                    for i in 0..4 {
+                        d.map_idx[i] = at + 1;
+                        at += usize::from(byte(bytes, at)?) + 1;
+                    }
+                }
+            }
+            at += at & 1;
+        }
+
+        for f in 0..files {
+            for side in items.iter_mut().take(sides) {
+                side[f].sparse_index = at;
+                at = at.checked_add(side[f].sparse_index_size as usize * 6)?;
+            }
+        }
+        for f in 0..files {
+            for side in items.iter_mut().take(sides) {
+                side[f].block_length = at;
+                at = at.checked_add(side[f].block_length_size as usize * 2)?;
+            }
+        }
+        for f in 0..files {
+            for side in items.iter_mut().take(sides) {
+                at = (at + 0x3f) & !0x3f;
+                side[f].data = at;
+                at = at.checked_add((side[f].num_blocks * side[f].block_size) as usize)?;
+            }
+        }
+
+        Some(items)
+    }
+
+    // groups the pieces the way they're indexed, and works out what each
+    // group multiplies the index by
+    fn set_groups(&self, d: &mut PairsData, order: [u8; 2], f: usize, ix: &Indices) -> Option<()> {
+        let mut n = 0;
+        let mut first_len: i32 = if self.has_pawns {
+            0
+        } else if self.has_unique_pieces {
+            3
+        } else {
+            2
+        };
+        d.group_len[0] = 1;
+        for i in 1..self.piece_count {
+            first_len -= 1;
+            if first_len > 0 || d.pieces[i] == d.pieces[i - 1] {
+                d.group_len[n] += 1;
+            } else {
+                n += 1;
+                d.group_len[n] = 1;
+            }
+        }
+        n += 1;
+        d.group_len[n] = 0;
+
+        let pp = self.has_pawns && self.pawn_count[1] > 0;
+        let mut next = if pp { 2 } else { 1 };
+        let mut free = 64usize.checked_sub(d.group_len[0] + if pp { d.group_len[1] } else { 0 })?;
+        let mut idx: u64 = 1;
+
+        let mut k = 0;
+        while next < n || k == usize::from(order[0]) || k == usize::from(order[1]) {
+            if k == usize::from(order[0]) {
+                d.group_idx[0] = idx;
+                let size = if self.has_pawns {
+                    *ix.lead_pawns_size.get(d.group_len[0])?.get(f)?
+                } else if self.has_unique_pieces {
+                    31332
+                } else {
+                    462
+                };
+                idx = idx.checked_mul(size)?;
+            } else if k == usize::from(order[1]) {
+                d.group_idx[1] = idx;
+                let size = *ix.binomial.get(d.group_len[1])?.get(48 - d.group_len[0])?;
+                idx = idx.checked_mul(size)?;
+            } else {
+                d.group_idx[next] = idx;
+                idx = idx.checked_mul(*ix.binomial.get(d.group_len[next])?.get(free)?)?;
+                free = free.checked_sub(d.group_len[next])?;
+                next += 1;
+            }
+            k += 1;
+        }
+        d.group_idx[n] = idx;
+        Some(())
+    }
+}
+
+// reads the sizes and the Huffman tree of one side, returns where the next
+// one starts
+fn set_sizes(d: &mut PairsData, bytes: &[u8], mut at: usize) -> Option<usize> {
+    d.flags = byte(bytes, at)?;
+    at += 1;
+
+    if d.flags & SINGLE_VALUE != 0 {
+        d.min_sym_len = byte(bytes, at)?;
+        return Some(at + 1);
+    }
+
+    let groups = d.group_len.iter().position(|&len| len == 0)?;
+    let tb_size = d.group_idx[groups];
+
+    let (block_shift, span_shift) = (byte(bytes, at)?, byte(bytes, at + 1)?);
+    if block_shift >= 32 || span_shift >= 32 {
+        return None;
+    }
+    d.block_size = 1 << block_shift;
+    d.span = 1 << span_shift;
+    d.sparse_index_size = (tb_size + d.span - 1) / d.span;
+    let padding = byte(bytes, at + 2)?;
+    d.num_blocks = u64::from(u32_le(bytes, at + 3)?);
+    d.block_length_size = d.num_blocks + u64::from(padding);
+    at += 7;
+
+    let max_sym_len = byte(bytes, at)?;
+    d.min_sym_len = byte(bytes, at + 1)?;
+    at += 2;
+    if max_sym_len < d.min_sym_len {
+        return None;
+    }
+    d.lowest_sym = at;
+
+    // base64[i] is the lowest code of length i + min_sym_len, left aligned
+    let len = usize::from(max_sym_len - d.min_sym_len) + 1;
+    d.base64 = vec![0; len];
+    for i in (0..len - 1).rev() {
+        let lowest = u64::from(u16_le(bytes, d.lowest_sym + 2 * i)?);
+        let next_lowest = u64::from(u16_le(bytes, d.lowest_sym + 2 * (i + 1))?);
+        d.base64[i] = d.base64[i + 1]
+            .wrapping_add(lowest)
+            .wrapping_sub(next_lowest)
+            / 2;
+    }
+    for (i, base) in d.base64.iter_mut().enumerate() {
+        let shift = 64usize.checked_sub(i + usize::from(d.min_sym_len))?;
+        *base = base.checked_shl(shift as u32).unwrap_or(0);
+    }
+    at += len * 2;
+
+    let count = usize::from(u16_le(bytes, at)?);
+    at += 2;
+    d.btree = at;
+    d.symlen = vec![0; count];
+    let mut visited = vec![false; count];
+    for sym in 0..count {
+        if !visited[sym] {
+            d.symlen[sym] = set_symlen(d, bytes, sym, &mut visited)?;
+        }
+    }
+
+    Some(at + count * 3 + (count & 1))
+}
+
+// how many values a symbol stands for, minus one
+fn set_symlen(d: &mut PairsData, bytes: &[u8], sym: usize, visited: &mut [bool]) -> Option<u32> {
+    *visited.get_mut(sym)? = true;
+
+    let (left, right) = pair(bytes, d.btree, sym)?;
+    if right == 0xfff {
+        return Some(0);
+    }
+    if !*visited.get(left)? {
+        d.symlen[left] = set_symlen(d, bytes, left, visited)?;
+    }
+    if !*visited.get(right)? {
+        d.symlen[right] = set_symlen(d, bytes, right, visited)?;
+    }
+    Some(d.symlen[left] + d.symlen[right] + 1)
+}
+
+// the value at index `idx` of one side of a table
+fn decompress_pairs(d: &PairsData, bytes: &[u8], idx: u64) -> Option<u32> {
+    if d.flags & SINGLE_VALUE != 0 {
+        return Some(u32::from(d.min_sym_len));
+    }
+
+    // the sparse index says which block `idx` is in, roughly, and how far
+    // into it
+    let k = (idx / d.span) as usize;
+    let entry = d.sparse_index + 6 * k;
+    let mut block = i64::from(u32_le(bytes, entry)?);
+    let mut offset = i64::from(u16_le(bytes, entry + 4)?);
+    offset += (idx % d.span) as i64 - (d.span / 2) as i64;
+
+    let block_length = |block: i64| -> Option<i64> {
+        if block < 0 || block as u64 >= d.block_length_size {
+            return None;
+        }
+        Some(i64::from(u16_le(
+            bytes,
+            d.block_length + 2 * block as usize,
+        )?))
+    };
+    while offset < 0 {
+        block -= 1;
+        offset += block_length(block)? + 1;
+    }
+    while offset > block_length(block)? {
+        offset -= block_length(block)? + 1;
+        block += 1;
+    }
+
+    let mut ptr = d.data + block as usize * d.block_size as usize;
+    let mut buf64 = u64_be(bytes, ptr)?;
+    ptr += 8;
+    let mut buf64_size: i64 = 64;
+    let min_sym_len = usize::from(d.min_sym_len);
+
+    let mut sym;
+    loop {
+        let mut len = 0;
+        while buf64 < *d.base64.get(len)? {
+            len += 1;
+        }
+        let shift = 64usize.checked_sub(len + min_sym_len)?;
+        sym = (buf64
+            .wrapping_sub(d.base64[len])
+            .checked_shr(shift as u32)
+            .unwrap_or(0)) as u16;
+        sym = sym.wrapping_add(u16_le(bytes, d.lowest_sym + 2 * len)?);
+        let symlen = i64::from(*d.symlen.get(usize::from(sym))?);
+
+        if offset < symlen + 1 {
+            break;
+        }
+        offset -= symlen + 1;
+
+        let bits = len + min_sym_len;
+        buf64 = buf64.checked_shl(bits as u32).unwrap_or(0);
+        buf64_size -= bits as i64;
+        if buf64_size < 0 {
+            return None;
+        }
+        if buf64_size <= 32 {
+            buf64_size += 32;
+            buf64 |= u64::from(u32_be(bytes, ptr)?) << (64 - buf64_size);
+            ptr += 4;
+        }
+    }
+
+    // walk down the tree to the value `offset` is at
+    let mut sym = usize::from(sym);
+    while *d.symlen.get(sym)? != 0 {
+        let (left, right) = pair(bytes, d.btree, sym)?;
+        let left_len = i64::from(*d.symlen.get(left)?);
+        if offset < left_len + 1 {
+            sym = left;
+        } else {
+            offset -= left_len + 1;
+            sym = right;
+        }
+    }
+    Some(pair(bytes, d.btree, sym)?.0 as u32)
+}
+
+const ROLES: [Role; 6] = [
+    Role::Pawn,
+    Role::Knight,
+    Role::Bishop,
+    Role::Rook,
+    Role::Queen,
+    Role::King,
+];
+
+// The material as a number, so the search finds the table of a position
+// without building its name: 4 bits for how many there are of each piece,
+// white's in the low 24 bits and black's above.
+fn material_key(board: &Board) -> u64 {
+    let mut key = 0;
+    for (color, shift) in [(Color::White, 0), (Color::Black, 24)].iter() {
+        for role in ROLES.iter() {
+            let count = (board.by_color(*color) & board.by_role(*role)).count() as u64;
+            key += count << (shift + (*role as u64 - 1) * 4);
+        }
+    }
+    key
+}
+
+// the same for a file name like KRvK, None if it isn't one
+fn name_key(name: &str) -> Option<u64> {
+    let mut sides = name.splitn(2, 'v');
+    let mut key = 0;
+    for shift in &[0, 24] {
+        for c in sides.next()?.chars() {
+            let role = Role::from_char(c.to_ascii_lowercase())?;
+            key += 1 << (shift + (role as u64 - 1) * 4);
+        }
+    }
+    Some(key)
+}
+
+// with the colors swapped
+fn mirror_key(key: u64) -> u64 {
+    (key >> 24) | ((key & 0xff_ffff) << 24)
+}
+
+// the pieces as Stockfish numbers them: 1 to 6 for white, 9 to 14 for black
+fn piece_code(game: &Chess, square: Square) -> Option<u8> {
+    let piece = game.board().piece_at(square)?;
+    Some(piece.role as u8 + piece.color.fold(0, 8))
+}
+
+enum Probe {
+    // and the file of the leading pawn, which DTZ values depend on
+    Value(u32, usize),
+    // a DTZ table only has one side, this is the other one
+    ChangeStm,
+}
+
+// the index of the position in the table, and the value there
+fn probe_table(table: &Table, game: &Chess, ix: &Indices) -> Option<Probe> {
+    let black_to_move = game.turn() == Color::Black;
+
+    // the table is for white having the material in its name, so when it's
+    // black that has it (or it's symmetric and black to move) everything is
+    // flipped
+    let flip =
+        (table.key == table.key2 && black_to_move) || material_key(game.board()) != table.key;
+    let flip_color = if flip { 8 } else { 0 };
+    let flip_squares = if flip { 56 } else { 0 };
+    let stm = usize::from(flip) ^ usize::from(black_to_move);
+
+    let mut squares = [0usize; MAX_PIECES];
+    let mut pieces = [0u8; MAX_PIECES];
+    let mut size = 0;
+    let mut lead_pawns = 0;
+    let mut tb_file = 0;
+
+    let occupied: Vec<(usize, u8)> = game
+        .board()
+        .occupied()
+        .into_iter()
+        .filter_map(|square| Some((usize::from(square), piece_code(game, square)?)))
+        .collect();
+    if occupied.len() > MAX_PIECES {
+        return None;
+    }
+
+    let lead_piece = table.items[0][0].pieces[0] ^ flip_color;
+    if table.has_pawns {
+        for &(square, code) in &occupied {
+            if code == lead_piece {
+                squares[size] = square ^ flip_squares;
+                pieces[size] = code ^ flip_color;
+                size += 1;
+            }
+        }
+        lead_pawns = size;
+        if lead_pawns == 0 {
+            return None;
+        }
+
+        // the leading pawn is the one nearest the a file
+        let mut lead = 0;
+        for i in 1..lead_pawns {
+            if ix.map_pawns[squares[i]] > ix.map_pawns[squares[lead]] {
+                lead = i;
+            }
+        }
+        squares.swap(0, lead);
+
+        tb_file = file_of(squares[0]);
+        if tb_file > 3 {
+            tb_file = file_of(squares[0] ^ 7);
+        }
+    }
+
+    if table.kind == Kind::Dtz {
+        let flags = table.items[0][tb_file].flags;
+        // a symmetric table without pawns is the same for both sides
+        let symmetric = table.key == table.key2 && !table.has_pawns;
+        if usize::from(flags & STM) != stm && !symmetric {
+            return Some(Probe::ChangeStm);
+        }
+    }
+
+    for &(square, code) in &occupied {
+        if table.has_pawns && code == lead_piece {
+            continue;
+        }
+        squares[size] = square ^ flip_squares;
+        pieces[size] = code ^ flip_color;
+        size += 1;
+    }
+    if size != table.piece_count {
+        return None;
+    }
+
+    let sides = match table.kind {
+        Kind::Wdl => 2,
+        Kind::Dtz => 1,
+    };
+    let d = &table.items[stm % sides][tb_file];
+
+    // put the pieces in the order of the table
+    for i in lead_pawns..size.saturating_sub(1) {
+        for j in i + 1..size {
+            if d.pieces[i] == pieces[j] {
+                pieces.swap(i, j);
+                squares.swap(i, j);
+                break;
+            }
+        }
+    }
+
+    // the leading piece goes to the a-d files
+    if file_of(squares[0]) > 3 {
+        for square in squares.iter_mut().take(size) {
+            *square ^= 7;
+        }
+    }
+
+    let mut idx: u64;
+    if table.has_pawns {
+        idx = ix.lead_pawn_idx[lead_pawns][squares[0]];
+        squares[1..lead_pawns].sort_by_key(|&square| ix.map_pawns[square]);
+        for (i, &square) in squares.iter().enumerate().take(lead_pawns).skip(1) {
+            idx += ix.binomial[i][ix.map_pawns[square]];
+        }
+    } else {
+        // without pawns the board is symmetric, the leading piece goes to
+        // the bottom half and then below the diagonal
+        if rank_of(squares[0]) > 3 {
+            for square in squares.iter_mut().take(size) {
+                *square ^= 56;
+            }
+        }
+        for i in 0..d.group_len[0] {
+            if off_diagonal(squares[i]) == 0 {
+                continue;
+            }
+            if off_diagonal(squares[i]) > 0 {
+                for square in squares.iter_mut().take(size).skip(i) {
+                    *square = flip_diagonal(*square);
+                }
+            }
+            break;
+        }
+
+        let (s0, s1, s2) = (squares[0], squares[1], squares[2]);
+        if table.has_unique_pieces {
+            let adjust1 = (s1 > s0) as usize;
+            let adjust2 = (s2 > s0) as usize + (s2 > s1) as usize;
+            idx = if off_diagonal(s0) != 0 {
+                ((ix.map_a1d1d4[s0] * 63 + (s1 - adjust1)) * 62 + s2 - adjust2) as u64
+            } else if off_diagonal(s1) != 0 {
+                (6 * 63 + rank_of(s0) as u64 * 28 + ix.map_b1h1h7[s1]) * 62 + (s2 - adjust2) as u64
+            } else if off_diagonal(s2) != 0 {
+                6 * 63 * 62
+                    + 4 * 28 * 62
+                    + rank_of(s0) as u64 * 7 * 28
+                    + (rank_of(s1) - adjust1) as u64 * 28
+                    + ix.map_b1h1h7[s2]
+            } else {
+                (6 * 63 * 62
+                    + 4 * 28 * 62
+                    + 4 * 7 * 28
+                    + rank_of(s0) * 7 * 6
+                    + rank_of(s1).checked_sub(adjust1)? * 6
+                    + rank_of(s2).checked_sub(adjust2)?) as u64
+            };
+        } else {
+            idx = ix.map_kk[ix.map_a1d1d4[s0]][s1];
+        }
+    }
+
+    // then the other groups, each the combination of its squares among the
+    // ones still free
+    idx = idx.checked_mul(d.group_idx[0])?;
+    let mut start = d.group_len[0];
+    let mut remaining_pawns = table.has_pawns && table.pawn_count[1] > 0;
+    let mut next = 1;
+    while next < d.group_len.len() && d.group_len[next] != 0 {
+        let len = d.group_len[next];
+        if start + len > size {
+            return None;
+        }
+        squares[start..start + len].sort_unstable();
+
+        let mut n: u64 = 0;
+        for i in 0..len {
+            let square = squares[start + i];
+            let adjust = squares[..start].iter().filter(|&&s| square > s).count();
+            let free = square
+                .checked_sub(adjust)?
+                .checked_sub(if remaining_pawns { 8 } else { 0 })?;
+            n += ix.binomial.get(i + 1)?.get(free)?;
+        }
+        remaining_pawns = false;
+        idx = idx.checked_add(n.checked_mul(d.group_idx[next])?)?;
+        start += len;
+        next += 1;
+    }
+
+    Some(Probe::Value(
+        decompress_pairs(d, &table.bytes, idx)?,
+        tb_file,
+    ))
+}
+
+// a zeroing move into a won position is a DTZ of 1, or 101 if the win
+// is cursed
+fn dtz_before_zeroing(wdl: Wdl) -> i32 {
+    match wdl {
+        Wdl::Win => 1,
+        Wdl::CursedWin => 101,
+        Wdl::Draw => 0,
+        Wdl::BlessedLoss => -101,
+        Wdl::Loss => -1,
+    }
+}
+
+// a table's material and whether it's the WDL or DTZ one
+type TableKey = (u64, Kind);
+
+pub struct Tablebase {
+    // the table files found, by the material in their name (`name_key`)
+    files: HashMap<TableKey, PathBuf>,
+    // the ones read so far, None if they couldn't be
+    tables: RefCell<HashMap<TableKey, Option<Rc<Table>>>>,
+    // why the first one that couldn't be read wasn't, for the caller to
+    // take
+    error: RefCell<Option<Error>>,
+    indices: Indices,
+    max_pieces: usize,
+    hits: Cell<u64>,
+}
+
+impl Tablebase {
+    // `path` is a directory with .rtbw and .rtbz files, or several of them
+    // separated like in $PATH. The tables are only read when they're first
+    // needed.
+    pub fn open(path: &Path) -> Result<Tablebase, Error> {
+        let mut files = HashMap::new();
+        let mut max_pieces = 0;
+
+        for dir in env::split_paths(path) {
+            let entries = fs::read_dir(&dir).map_err(|e| Error::io(dir.display(), e))?;
+            for entry in entries {
+                let path = entry.map_err(|e| Error::io(dir.display(), e))?.path();
+                let (stem, extension) = match (path.file_stem(), path.extension()) {
+                    (Some(stem), Some(extension)) => (stem.to_string_lossy(), extension),
+                    _ => continue,
+                };
+                let valid_name = stem.len() <= MAX_PIECES + 1
+                    && stem.matches('v').count() == 1
+                    && stem.split('v').all(|side| {
+                        side.starts_with('K') && side.chars().all(|c| "KQRBNP".contains(c))
+                    });
+                let kind = match [Kind::Wdl, Kind::Dtz]
+                    .iter()
+                    .copied()
+                    .find(|kind| extension == kind.extension())
+                {
+                    Some(kind) => kind,
+                    None => continue,
+                };
+                let key = match name_key(&stem) {
+                    Some(key) if valid_name => key,
+                    _ => continue,
+                };
+                if kind == Kind::Wdl {
+                    max_pieces = max_pieces.max(stem.len() - 1);
+                }
+                files.entry((key, kind)).or_insert(path);
+            }
+        }
+
+        if max_pieces == 0 {
+            return Err(Error::asset(path, "no Syzygy tables (.rtbw files) found"));
+        }
+
+        Ok(Tablebase {
+            files,
+            tables: RefCell::new(HashMap::new()),
+            error: RefCell::new(None),
+            indices: Indices::new(),
+            max_pieces,
+            hits: Cell::new(0),
+        })
+    }
+
+    // the most pieces of any table, positions with more aren't probed
+    pub fn max_pieces(&self) -> usize {
+        self.max_pieces
+    }
+
+    // how many probes found an answer, for the search statistics
+    pub fn hits(&self) -> u64 {
+        self.hits.get()
+    }
+
+    // A table that turned out to be unreadable when it was first needed,
+    // since the last call. The positions in it are probed as if it wasn't
+    // there.
+    pub fn take_error(&self) -> Option<Error> {
+        self.error.borrow_mut().take()
+    }
+
+    fn covers(&self, game: &Chess) -> bool {
+        game.board().occupied().count() <= self.max_pieces && !game.castling_rights().any()
+    }
+
+    fn table(&self, game: &Chess, kind: Kind) -> Option<Rc<Table>> {
+        let key = material_key(game.board());
+        let key = [(key, kind), (mirror_key(key), kind)]
+            .iter()
+            .copied()
+            .find(|key| self.files.contains_key(key))?;
+        if let Some(table) = self.tables.borrow().get(&key) {
+            return table.clone();
+        }
+
+        let path = &self.files[&key];
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        let table = fs::read(path)
+            .map_err(|e| Error::io(path.display(), e))
+            .and_then(|bytes| {
+                Table::new(&stem, kind, bytes, &self.indices)
+                    .ok_or_else(|| Error::asset(path, "not a Syzygy table, or a broken one"))
+            });
+        let table = match table {
+            Ok(table) => Some(Rc::new(table)),
+            Err(err) => {
+                self.error.borrow_mut().get_or_insert(err);
+                None
+            }
+        };
+        self.tables.borrow_mut().insert(key, table.clone());
+        table
+    }
+
+    fn probe_table_wdl(&self, game: &Chess) -> Option<Wdl> {
+        // KvK isn't in a file
+        if game.board().occupied().count() == 2 {
+            return Some(Wdl::Draw);
+        }
+        let table = self.table(game, Kind::Wdl)?;
+        match probe_table(&table, game, &self.indices)? {
+            Probe::Value(value, _) => Wdl::from_value(value as i32 - 2),
+            Probe::ChangeStm => None,
+        }
+    }
+
+    fn probe_table_dtz(&self, game: &Chess, wdl: Wdl) -> Option<Probe> {
+        let table = self.table(game, Kind::Dtz)?;
+        match probe_table(&table, game, &self.indices)? {
+            Probe::Value(value, tb_file) => {
+                map_score(&table, tb_file, value, wdl).map(|dtz| Probe::Value(dtz, tb_file))
+            }
+            Probe::ChangeStm => Some(Probe::ChangeStm),
+        }
+    }
+
+    // the best result the side to move can get from the captures (and with
+    // `zeroing`, the pawn moves) and from the table, and whether that's
+    // by zeroing the 50-move counter
+    fn search(&self, game: &Chess, zeroing: bool) -> Option<(Wdl, bool)> {
+        let legals = game.legals();
+        let mut best = Wdl::Loss;
+        let mut count = 0;
+
+        for m in &legals {
+            if !m.is_capture() && (!zeroing || m.role() != Role::Pawn) {
+                continue;
+            }
+            count += 1;
+
+            let mut after = game.clone();
+            after.play_unchecked(m);
+            let value = -self.search(&after, false)?.0;
+
+            if value > best {
+                best = value;
+                if value == Wdl::Win {
+                    return Some((value, true));
+                }
+            }
+        }
+
+        // with only captures to play there's nothing to look up
+        let no_more_moves = count > 0 && count == legals.len();
+        let value = if no_more_moves {
+            best
+        } else {
+            self.probe_table_wdl(game)?
+        };
+
+        if best >= value {
+            Some((best, best > Wdl::Draw || no_more_moves))
+        } else {
+            Some((value, false))
+        }
+    }
+
+    fn dtz(&self, game: &Chess) -> Option<i32> {
+        let (wdl, zeroing) = self.search(game, true)?;
+        if wdl == Wdl::Draw {
+            return Some(0);
+        }
+        if zeroing {
+            return Some(dtz_before_zeroing(wdl));
+        }
+
+        match self.probe_table_dtz(game, wdl)? {
+            Probe::Value(dtz, _) => {
+                let cursed = wdl == Wdl::CursedWin || wdl == Wdl::BlessedLoss;
+                Some((dtz as i32 + if cursed { 100 } else { 0 }) * wdl.signum())
+            }
+            // the table is for the other side: the best of the moves, one
+            // ply further
+            Probe::ChangeStm => {
+                let mut min_dtz = 0xffff;
+                for m in &game.legals() {
+                    let zeroing = m.is_capture() || m.role() == Role::Pawn;
+                    let mut after = game.clone();
+                    after.play_unchecked(m);
+
+                    let mut dtz = if zeroing {
+                        -dtz_before_zeroing(self.search(&after, false)?.0)
+                    } else {
+                        -self.dtz(&after)?
+                    };
+                    if dtz == 1 && after.is_checkmate() {
+                        min_dtz = 1;
+                    }
+                    if !zeroing {
+                        dtz += dtz.signum();
+                    }
+                    if dtz < min_dtz && dtz.signum() == wdl.signum() {
+                        min_dtz = dtz;
+                    }
+                }
+                Some(if min_dtz == 0xffff { -1 } else { min_dtz })
+            }
+        }
+    }
+
+    // None when the position isn't in the tables, or has castling rights
+    pub fn probe_wdl(&self, game: &Chess) -> Option<Wdl> {
+        if !self.covers(game) {
+            return None;
+        }
+        let (wdl, _) = self.search(game, false)?;
+        self.hits.set(self.hits.get() + 1);
+        Some(wdl)
+    }
+
+    // Plies to the next capture or pawn move when playing it out perfectly,
+    // positive for wins and negative for losses, 0 for draws. Cursed wins
+    // and blessed losses are 100 further.
+    pub fn probe_dtz(&self, game: &Chess) -> Option<i32> {
+        if !self.covers(game) {
+            return None;
+        }
+        let dtz = self.dtz(game)?;
+        self.hits.set(self.hits.get() + 1);
+        Some(dtz)
+    }
+
+    // The moves that keep the best result the tables know of, taking the
+    // 50-move rule into account: the quickest win, the slowest loss, or
+    // every move that holds the draw, for the search to pick from. None if
+    // the position can't be probed.
+    pub fn best_moves(&self, game: &Chess) -> Option<Vec<Move>> {
+        if !self.covers(game) {
+            return None;
+        }
+
+        let halfmoves = game.halfmoves() as i32;
+        let mut ranked = Vec::new();
+        for m in &game.legals() {
+            let mut after = game.clone();
+            after.play_unchecked(m);
+
+            let mut dtz = if m.is_capture() || m.role() == Role::Pawn {
+                dtz_before_zeroing(-self.search(&after, false)?.0)
+            } else {
+                let dtz = -self.dtz(&after)?;
+                dtz + dtz.signum()
+            };
+            if dtz == 2 && after.is_checkmate() {
+                dtz = 1;
+            }
+
+            // wins that can still be won before the 50-move rule first,
+            // then the ones that come closest
+            let rank = if dtz > 0 {
+                if dtz + halfmoves <= 99 {
+                    1000
+                } else {
+                    1000 - (dtz + halfmoves)
+                }
+            } else if dtz < 0 {
+                if -dtz * 2 + halfmoves < 100 {
+                    -1000
+                } else {
+                    -1000 + (-dtz + halfmoves)
+                }
+            } else {
+                0
+            };
+            ranked.push((m.clone(), rank, dtz));
+        }
+        self.hits.set(self.hits.get() + 1);
+
+        let best = ranked.iter().map(|&(_, rank, _)| rank).max()?;
+        let mut best_moves: Vec<(Move, i32)> = ranked
+            .into_iter()
+            .filter(|&(_, rank, _)| rank == best)
+            .map(|(m, _, dtz)| (m, dtz))
+            .collect();
+        if best != 0 {
+            // when winning the shortest way makes progress, when losing the
+            // longest one gives the other side the most chances to go wrong
+            best_moves.sort_by_key(|&(_, dtz)| dtz);
+            best_moves.truncate(1);
+        }
+        Some(best_moves.into_iter().map(|(m, _)| m).collect())
+    }
+}
+
+// a DTZ table stores the distance, halved and remapped where it can be
+fn map_score(table: &Table, tb_file: usize, value: u32, wdl: Wdl) -> Option<u32> {
+    let d = &table.items[0][tb_file];
+    let mut value = value;
+
+    if d.flags & MAPPED != 0 {
+        let map = d.map_idx[match wdl {
+            Wdl::Win | Wdl::Draw => 0,
+            Wdl::Loss => 1,
+            Wdl::CursedWin => 2,
+            Wdl::BlessedLoss => 3,
+        }];
+        value = if d.flags & WIDE != 0 {
+            u32::from(u16_le(&table.bytes, map + 2 * value as usize)?)
+        } else {
+            u32::from(byte(&table.bytes, map + value as usize)?)
+        };
+    }
+
+    if (wdl == Wdl::Win && d.flags & WIN_PLIES == 0)
+        || (wdl == Wdl::Loss && d.flags & LOSS_PLIES == 0)
+        || wdl == Wdl::CursedWin
+        || wdl == Wdl::BlessedLoss
+    {
+        value *= 2;
+    }
+    Some(value + 1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::notation::{self, position_from_fen};
+    use shakmaty::fen::Fen;
+    use std::process;
+
+    // A table where every position has the same value is the one kind
+    // small enough to write by hand: KQvK, a win for white to move and a
+    // loss for black to move, unless the queen hangs.
+    fn single_value_table() -> Vec<u8> {
+        let mut bytes = WDL_MAGIC.to_vec();
+        // different material on each side, no pawns
+        bytes.push(1);
+        // the group order, then the pieces (wK, wQ, bK) for both sides
+        bytes.extend(&[0x00, 0x66, 0x55, 0xee]);
+        bytes.push(0);
+        // the values, plus 2
+        bytes.extend(&[SINGLE_VALUE, 4, SINGLE_VALUE, 0]);
+        bytes
+    }
+
+    // The 3-piece tables from the official set, KQvK, KRvK and KPvK with
+    // both .rtbw and .rtbz, which go in tests/syzygy. The tests that need
+    // them are ignored until they're there:
+    //     cargo test syzygy -- --ignored
+    fn official_tables() -> Tablebase {
+        let dir = Path::new(env!("CARGO_MANIFEST_DIR")).join("tests/syzygy");
+        let tablebase = Tablebase::open(&dir).unwrap();
+        assert_eq!(tablebase.max_pieces(), 3);
+        tablebase
+    }
+
+    // A DTZ table can store moves instead of plies where the 50-move rule
+    // can't get in the way, and then an even distance comes out one more.
+    fn assert_dtz(found: Option<i32>, expected: i32, fen: &str) {
+        let found = found.unwrap_or_else(|| panic!("no DTZ for {}", fen));
+        let rounded = if expected % 2 == 0 {
+            expected + expected.signum()
+        } else {
+            expected
+        };
+        assert!(
+            found == expected || found == rounded,
+            "{}: DTZ {}, not {}",
+            fen,
+            found,
+            expected
+        );
+    }
+
+    #[test]
+    fn test_indices() {
+        let ix = Indices::new();
+        assert_eq!(ix.map_kk.iter().flatten().max(), Some(&461));
+        assert_eq!(ix.binomial[2][6], 15);
+
+        let mut pawns: Vec<usize> = (8..56).map(|s| ix.map_pawns[s]).collect();
+        pawns.sort_unstable();
+        assert_eq!(pawns, (0..48).collect::<Vec<_>>());
+    }
+
+    #[test]
+    fn test_material_key() {
+        let key = |fen: &str| material_key(position_from_fen(fen).unwrap().board());
+        assert_eq!(
+            Some(key("8/8/8/8/4k3/8/1R6/K7 b - - 0 1")),
+            name_key("KRvK")
+        );
+        assert_eq!(
+            Some(key("8/8/8/8/4K3/8/1r6/k7 w - - 0 1")),
+            name_key("KvKR")
+        );
+        assert_eq!(name_key("KvKR"), name_key("KRvK").map(mirror_key));
+        assert_eq!(
+            Some(key("8/2p5/8/8/4k3/8/1QN5/K7 b - - 0 1")),
+            name_key("KQNvKP")
+        );
+        assert_eq!(name_key("KRvKX"), None);
+    }
+
+    #[test]
+    fn test_probe_wdl() {
+        let dir = env::temp_dir().join(format!("chess-syzygy-{}", process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        assert!(Tablebase::open(&dir).is_err());
+
+        fs::write(dir.join("KQvK.rtbw"), single_value_table()).unwrap();
+        let tablebase = Tablebase::open(&dir).unwrap();
+        assert_eq!(tablebase.max_pieces(), 3);
+
+        let probe = |fen: &str| tablebase.probe_wdl(&position_from_fen(fen).unwrap());
+        assert_eq!(probe("8/8/8/8/8/8/1Q6/K6k w - - 0 1"), Some(Wdl::Win));
+        assert_eq!(probe("8/8/8/8/8/8/1Q6/K6k b - - 0 1"), Some(Wdl::Loss));
+        // the queen is taken
+        assert_eq!(probe("8/8/8/8/8/8/6Q1/K6k b - - 0 1"), Some(Wdl::Draw));
+        // the same table, black has the queen
+        assert_eq!(probe("8/8/8/8/8/8/1q6/k6K w - - 0 1"), Some(Wdl::Loss));
+        assert_eq!(probe("8/8/8/8/8/8/8/K6k w - - 0 1"), Some(Wdl::Draw));
+        assert_eq!(probe("8/8/8/8/8/8/1R6/K6k w - - 0 1"), None);
+        assert_eq!(tablebase.hits(), 5);
+        assert!(tablebase.take_error().is_none());
+
+        // a broken table is passed on once, and then left out
+        fs::write(dir.join("KRvK.rtbw"), WDL_MAGIC).unwrap();
+        let tablebase = Tablebase::open(&dir).unwrap();
+        let rook = position_from_fen("8/8/8/8/8/8/1R6/K6k w - - 0 1").unwrap();
+        assert_eq!(tablebase.probe_wdl(&rook), None);
+        assert!(tablebase
+            .take_error()
+            .unwrap()
+            .to_string()
+            .contains("KRvK.rtbw"));
+        assert_eq!(tablebase.probe_wdl(&rook), None);
+        assert!(tablebase.take_error().is_none());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    #[ignore]
+    fn test_probe_official_tables() {
+        let tablebase = official_tables();
+        let cases = [
+            // mate in one, mated, and the rook hangs
+            ("k7/8/1K6/8/8/8/8/7R w - - 0 1", Wdl::Win, 1),
+            ("R6k/8/6K1/8/8/8/8/8 b - - 0 1", Wdl::Loss, -1),
+            ("k7/1R6/8/8/8/8/8/K7 b - - 0 1", Wdl::Draw, 0),
+            // the same with the colors the other way round
+            ("K7/8/1k6/8/8/8/8/7r b - - 0 1", Wdl::Win, 1),
+            // the longest there is with a rook, mate in 16, and with a
+            // queen, mate in 10
+            ("8/8/8/8/8/2k5/1R6/K7 w - - 0 1", Wdl::Win, 31),
+            ("8/8/8/8/8/8/1Rk5/K7 b - - 0 1", Wdl::Loss, -32),
+            ("8/8/8/8/4k3/8/1Q6/K7 b - - 0 1", Wdl::Loss, -20),
+            ("k7/8/1K6/8/8/8/8/6Q1 w - - 0 1", Wdl::Win, 1),
+            // the pawn wins with the opposition, and a rook pawn only draws
+            ("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1", Wdl::Draw, 0),
+            ("8/4k3/8/4K3/4P3/8/8/8 b - - 0 1", Wdl::Loss, -4),
+            ("k7/8/K7/P7/8/8/8/8 w - - 0 1", Wdl::Draw, 0),
+            // Ke6 Kf8 Kd7 Kf7 e6+, and a pawn move zeroes the counter
+            ("4k3/8/3K4/4P3/8/8/8/8 w - - 0 1", Wdl::Win, 5),
+            ("8/8/8/8/8/8/P6k/K7 w - - 0 1", Wdl::Win, 1),
+            ("4k3/8/4K3/8/8/8/4p3/8 b - - 0 1", Wdl::Win, 1),
+        ];
+        for &(fen, wdl, dtz) in &cases {
+            let game = position_from_fen(fen).unwrap();
+            assert_eq!(tablebase.probe_wdl(&game), Some(wdl), "{}", fen);
+            assert_dtz(tablebase.probe_dtz(&game), dtz, fen);
+        }
+        assert!(tablebase.take_error().is_none());
+    }
+
+    #[test]
+    #[ignore]
+    fn test_best_moves() {
+        let tablebase = official_tables();
+        let best = |fen: &str| {
+            let game = position_from_fen(fen).unwrap();
+            let moves = tablebase.best_moves(&game).unwrap();
+            let mut moves: Vec<String> = moves.iter().map(|m| notation::san(&game, m)).collect();
+            moves.sort();
+            moves
+        };
+
+        assert_eq!(best("k7/8/1K6/8/8/8/8/7R w - - 0 1"), vec!["Rh8#"]);
+        assert_eq!(best("4k3/8/3K4/4P3/8/8/8/8 w - - 0 1"), vec!["Ke6"]);
+        // the draws: the only one, and all of them
+        assert_eq!(best("2k5/1R6/8/8/8/8/8/K7 b - - 0 1"), vec!["Kxb7"]);
+        assert_eq!(
+            best("8/4k3/8/4K3/4P3/8/8/8 w - - 0 1"),
+            vec!["Kd4", "Kd5", "Kf4", "Kf5"]
+        );
+    }
+
+    // The value of every so many positions of each table against the ones
+    // a move further: a table that was read wrong wouldn't add up. Either
+    // DTZ can be one more than it is, see `assert_dtz`.
+    #[test]
+    #[ignore]
+    fn test_probes_agree_with_one_ply_more() {
+        let tablebase = official_tables();
+        for &role in &[Role::Queen, Role::Rook, Role::Pawn] {
+            for id in (0..POSITIONS).step_by(101) {
+                let game = match position(role, id) {
+                    Some(game) => game,
+                    None => continue,
+                };
+                let fen = notation::to_fen(&game);
+                let (wdl, dtz) = one_ply(&tablebase, &game);
+                assert_eq!(tablebase.probe_wdl(&game), Some(wdl), "{}", fen);
+                let found = tablebase.probe_dtz(&game).unwrap();
+                assert!(
+                    found.signum() == dtz.signum() && (found - dtz).abs() <= 1,
+                    "{}: DTZ {}, {} a ply further",
+                    fen,
+                    found,
+                    dtz
+                );
+            }
+        }
+        assert!(tablebase.take_error().is_none());
+    }
+
+    // the result of `game` and its DTZ, from the ones of its moves
+    fn one_ply(tablebase: &Tablebase, game: &Chess) -> (Wdl, i32) {
+        let legals = game.legals();
+        if legals.is_empty() {
+            return if game.is_checkmate() {
+                (Wdl::Loss, -1)
+            } else {
+                (Wdl::Draw, 0)
+            };
+        }
+
+        let mut children = Vec::new();
+        for m in &legals {
+            let mut after = game.clone();
+            after.play_unchecked(m);
+            let wdl = -tablebase.probe_wdl(&after).unwrap();
+            children.push((m.is_capture() || m.role() == Role::Pawn, after, wdl));
+        }
+        let wdl = children.iter().map(|(_, _, wdl)| *wdl).max().unwrap();
+        if wdl == Wdl::Draw {
+            return (wdl, 0);
+        }
+
+        // the quickest win, or the slowest loss
+        let dtz = children
+            .iter()
+            .filter(|(_, _, child)| *child == wdl)
+            .map(|(zeroing, after, _)| match (zeroing, wdl) {
+                (true, Wdl::Win) => 1,
+                (true, _) => -1,
+                (false, Wdl::Win) if after.is_checkmate() => 1,
+                (false, _) => {
+                    let dtz = -tablebase.probe_dtz(after).unwrap();
+                    dtz + dtz.signum()
+                }
+            })
+            .min()
+            .unwrap();
+        (wdl, dtz)
+    }
+
+    // every placement of the white king, one more white piece and the black
+    // king, and the side to move
+    const POSITIONS: usize = 64 * 64 * 64 * 2;
+
+    fn position(role: Role, id: usize) -> Option<Chess> {
+        let squares = [id / 2 / 4096, id / 2 / 64 % 64, id / 2 % 64];
+        if squares[0] == squares[1] || squares[0] == squares[2] || squares[1] == squares[2] {
+            return None;
+        }
+
+        let mut fen = Fen::empty();
+        let pieces = [
+            Role::King.of(Color::White),
+            role.of(Color::White),
+            Role::King.of(Color::Black),
+        ];
+        for (&square, &piece) in squares.iter().zip(pieces.iter()) {
+            fen.board
+                .set_piece_at(Square::new(square as u32), piece, false);
+        }
+        fen.turn = if id & 1 == 0 {
+            Color::White
+        } else {
+            Color::Black
+        };
+        fen.position().ok()
+    }
+}
@@ -0,0 +1,377 @@
+extern crate shakmaty;
+
+use shakmaty::{Chess, Color, Outcome, Setup};
+
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::book;
+use crate::config::Config;
+use crate::controller::{Event, GameController, GameResult};
+use crate::error::Error;
+use crate::notation;
+use crate::pgn;
+use crate::player::PlayerSpec;
+
+// One side of a match: who plays and with which settings (depth, movetime,
+// eval parameters, book...).
+#[derive(Clone, Debug)]
+pub struct Contestant {
+    pub name: String,
+    pub spec: PlayerSpec,
+    pub config: Config,
+}
+
+// When a game is called before it's over.
+#[derive(Clone, Copy, Debug)]
+pub struct Adjudication {
+    // a draw after this many moves, 0 to play on forever
+    pub max_moves: u32,
+    // a win once both engines agree one side is this many centipawns up,
+    // 0 for never
+    pub win_score: i32,
+    // for this many moves in a row of each side
+    pub win_moves: usize,
+}
+
+impl Default for Adjudication {
+    fn default() -> Adjudication {
+        Adjudication {
+            max_moves: 150,
+            win_score: 1000,
+            win_moves: 4,
+        }
+    }
+}
+
+// how a game ended, the way `GameResult` prints it or why it was called
+pub struct Played {
+    pub outcome: Outcome,
+    pub reason: String,
+    pub pgn: String,
+}
+
+// Plays one game from `start` to the end, or until `rules` call it.
+// Repetitions and the fifty-move rule end it too, the controller leaves
+// those to the players.
+pub fn play_game(
+    white: &Contestant,
+    black: &Contestant,
+    start: &Chess,
+    rules: &Adjudication,
+    round: usize,
+) -> Result<Played, Error> {
+    let mut controller = GameController::new(
+        start.clone(),
+        white
+            .spec
+            .create(&white.config, start, Color::White, |_| {})?,
+        black
+            .spec
+            .create(&black.config, start, Color::Black, |_| {})?,
+    );
+    if let Some((secs, increment)) = white.config.clock {
+        controller =
+            controller.with_clock(Duration::from_secs(secs), Duration::from_secs(increment));
+    }
+
+    let mut seen: HashMap<u64, u32> = HashMap::new();
+    *seen.entry(book::key(start)).or_insert(0) += 1;
+    // white's point of view, as each engine saw it after its move
+    let mut scores: Vec<Option<i32>> = Vec::new();
+    let mut reason = None;
+
+    while controller.result().is_none() {
+        let turn = controller.position().turn();
+        controller.update();
+
+        while let Some(event) = controller.poll_event() {
+            if let Event::Moved { by, .. } = event {
+                let thinking = controller.player(by).thinking();
+                scores.push(
+                    thinking
+                        .filter(|thinking| !thinking.book)
+                        .map(|thinking| by.fold(thinking.score, -thinking.score)),
+                );
+            }
+        }
+        if controller.result().is_some() {
+            break;
+        }
+        if controller.position().turn() == turn {
+            // an engine in another process
+            thread::sleep(Duration::from_millis(1));
+            continue;
+        }
+
+        let position = controller.position();
+        let repeated = seen.entry(book::key(position)).or_insert(0);
+        *repeated += 1;
+
+        let call = if *repeated >= 3 {
+            Some((Outcome::Draw, "threefold repetition"))
+        } else if position.halfmoves() >= 100 {
+            Some((Outcome::Draw, "fifty-move rule"))
+        } else if rules.max_moves > 0 && scores.len() >= 2 * rules.max_moves as usize {
+            Some((Outcome::Draw, "move limit"))
+        } else {
+            agreed_winner(&scores, rules)
+                .map(|winner| (Outcome::Decisive { winner }, "both engines agree"))
+        };
+
+        if let Some((outcome, why)) = call {
+            controller.adjudicate(outcome);
+            reason = Some(why.to_owned());
+        }
+    }
+
+    let result: GameResult = controller.result().expect("the game is over");
+    let fen = notation::to_fen(start);
+    let moves: Vec<String> = controller.moves().iter().map(|m| m.to_string()).collect();
+    let pgn = pgn::write(
+        &white.name,
+        &black.name,
+        if fen == notation::STARTING_FEN {
+            None
+        } else {
+            Some(&fen)
+        },
+        start.fullmoves(),
+        start.turn() == Color::Black,
+        &moves,
+        pgn::result(Some(result.outcome)),
+    )
+    .replacen(
+        "[Event \"Casual game\"]\n",
+        &format!("[Event \"Engine match\"]\n[Round \"{}\"]\n", round),
+        1,
+    );
+
+    Ok(Played {
+        outcome: result.outcome,
+        reason: reason.unwrap_or_else(|| result.to_string()),
+        pgn,
+    })
+}
+
+// the side both engines have thought was winning for the last few moves
+fn agreed_winner(scores: &[Option<i32>], rules: &Adjudication) -> Option<Color> {
+    let plies = 2 * rules.win_moves;
+    if rules.win_score <= 0 || plies == 0 || scores.len() < plies {
+        return None;
+    }
+
+    let last = &scores[scores.len() - plies..];
+    if last
+        .iter()
+        .all(|score| score.is_some_and(|s| s >= rules.win_score))
+    {
+        Some(Color::White)
+    } else if last
+        .iter()
+        .all(|score| score.is_some_and(|s| s <= -rules.win_score))
+    {
+        Some(Color::Black)
+    } else {
+        None
+    }
+}
+
+// The positions of an opening suite, one FEN or EPD per line (anything after
+// the position is left alone).
+pub fn load_openings(path: &Path) -> Result<Vec<Chess>, Error> {
+    let text = fs::read_to_string(path).map_err(|e| Error::io(path.display(), e))?;
+    let mut openings = Vec::new();
+
+    for (i, line) in text.lines().enumerate() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            continue;
+        }
+        let words: Vec<&str> = line.split_whitespace().collect();
+        let mut fields = words.len().min(4);
+        while fields < words.len().min(6) && words[fields].parse::<u32>().is_ok() {
+            fields += 1;
+        }
+        let opening = notation::position_from_fen(&words[..fields].join(" "))
+            .map_err(|e| Error::Config(format!("{}:{}: {}", path.display(), i + 1, e)))?;
+        openings.push(opening);
+    }
+
+    Ok(openings)
+}
+
+// Wins, draws and losses of the first contestant.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Tally {
+    pub wins: u32,
+    pub draws: u32,
+    pub losses: u32,
+}
+
+// what scoring `score` out of 1 is worth, in Elo
+fn elo(score: f64) -> f64 {
+    400.0 * (score / (1.0 - score)).log10()
+}
+
+impl Tally {
+    pub fn add(&mut self, outcome: Outcome, first_color: Color) {
+        match outcome {
+            Outcome::Decisive { winner } if winner == first_color => self.wins += 1,
+            Outcome::Decisive { .. } => self.losses += 1,
+            Outcome::Draw => self.draws += 1,
+        }
+    }
+
+    pub fn games(&self) -> u32 {
+        self.wins + self.draws + self.losses
+    }
+
+    // the points per game
+    pub fn score(&self) -> f64 {
+        (self.wins as f64 + self.draws as f64 / 2.0) / self.games().max(1) as f64
+    }
+
+    // how much one game's points vary
+    fn variance(&self) -> f64 {
+        let (n, p) = (self.games().max(1) as f64, self.score());
+        (self.wins as f64 * (1.0 - p).powi(2)
+            + self.draws as f64 * (0.5 - p).powi(2)
+            + self.losses as f64 * p.powi(2))
+            / n
+    }
+
+    // The Elo difference and the margin of its 95% confidence interval,
+    // None while it's all wins or all losses (the difference is infinite).
+    pub fn elo(&self) -> Option<(f64, f64)> {
+        let p = self.score();
+        if self.games() == 0 || p <= 0.0 || p >= 1.0 {
+            return None;
+        }
+
+        let error = (self.variance() / self.games() as f64).sqrt();
+        let low = elo((p - 1.96 * error).max(1e-6));
+        let high = elo((p + 1.96 * error).min(1.0 - 1e-6));
+        Some((elo(p), (high - low) / 2.0))
+    }
+
+    // The log-likelihood ratio of the first contestant being `elo1` better
+    // rather than `elo0`, with every game's points taken as normally
+    // distributed (like fishtest and cutechess do).
+    // A win, a draw and a loss are added to the games so far, or a run of
+    // the same result would have no variance to divide by.
+    pub fn llr(&self, elo0: f64, elo1: f64) -> f64 {
+        if self.games() == 0 {
+            return 0.0;
+        }
+        let tally = Tally {
+            wins: self.wins + 1,
+            draws: self.draws + 1,
+            losses: self.losses + 1,
+        };
+
+        let expected = |elo: f64| 1.0 / (1.0 + 10f64.powf(-elo / 400.0));
+        let (s0, s1) = (expected(elo0), expected(elo1));
+        let n = tally.games() as f64;
+        let points = tally.score() * n;
+        (s1 - s0) * (2.0 * points - n * (s0 + s1)) / (2.0 * tally.variance())
+    }
+}
+
+// A sequential probability ratio test: the match goes on until it's clear
+// enough whether the first contestant is `elo1` better (H1) or only `elo0`
+// (H0), with the chances `alpha` and `beta` of getting it wrong.
+#[derive(Clone, Copy, Debug)]
+pub struct Sprt {
+    pub elo0: f64,
+    pub elo1: f64,
+    pub alpha: f64,
+    pub beta: f64,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    H0,
+    H1,
+}
+
+impl Sprt {
+    // the LLR below which H0 wins and above which H1 does
+    pub fn bounds(&self) -> (f64, f64) {
+        (
+            (self.beta / (1.0 - self.alpha)).ln(),
+            ((1.0 - self.beta) / self.alpha).ln(),
+        )
+    }
+
+    pub fn verdict(&self, tally: &Tally) -> Option<Verdict> {
+        let llr = tally.llr(self.elo0, self.elo1);
+        let (lower, upper) = self.bounds();
+        if llr <= lower {
+            Some(Verdict::H0)
+        } else if llr >= upper {
+            Some(Verdict::H1)
+        } else {
+            None
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_match() {
+        let tally = Tally {
+            wins: 30,
+            draws: 40,
+            losses: 30,
+        };
+        let (diff, margin) = tally.elo().unwrap();
+        assert!(diff.abs() < 1e-9 && margin > 30.0 && margin < 80.0);
+
+        let tally = Tally {
+            wins: 60,
+            draws: 20,
+            losses: 20,
+        };
+        assert!((tally.elo().unwrap().0 - 147.2).abs() < 0.1);
+        // clear enough only after ten times as many games
+        let more = Tally {
+            wins: 600,
+            draws: 200,
+            losses: 200,
+        };
+        let sprt = Sprt {
+            elo0: 0.0,
+            elo1: 10.0,
+            alpha: 0.05,
+            beta: 0.05,
+        };
+        assert_eq!(sprt.verdict(&tally), None);
+        assert_eq!(sprt.verdict(&more), Some(Verdict::H1));
+        assert_eq!(sprt.verdict(&Tally::default()), None);
+
+        // two quick engines, called a draw after three moves
+        let engine = Contestant {
+            name: "engine".to_owned(),
+            spec: PlayerSpec::Engine,
+            config: Config {
+                depth: 1,
+                ..Config::default()
+            },
+        };
+        let rules = Adjudication {
+            max_moves: 3,
+            ..Adjudication::default()
+        };
+        let played = play_game(&engine, &engine, &Chess::default(), &rules, 1).unwrap();
+        assert_eq!(played.outcome, Outcome::Draw);
+        assert_eq!(played.reason, "move limit");
+        assert!(played.pgn.contains("[Round \"1\"]") && played.pgn.contains("3."));
+    }
+}
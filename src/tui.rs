@@ -0,0 +1,491 @@
+extern crate shakmaty;
+
+use shakmaty::{Board, Chess, Color, File, Piece, Position, Rank, Role, Setup, Square};
+
+use std::io::{BufRead, Write};
+use std::path::Path;
+use std::thread;
+use std::time::Duration;
+
+use crate::config::Config;
+use crate::controller::{self, Event, GameController, Input};
+use crate::error::Error;
+use crate::eval;
+use crate::notation;
+use crate::perft;
+use crate::pgn;
+use crate::skill::{self, Skill};
+
+pub const COMMANDS: &str = "\
+Type moves in SAN (Nf3, exd5, O-O, e8=Q) or UCI notation (g1f3, e7e8q).
+Other commands:
+    moves         list the legal moves
+    eval          show how the engine scores the position, term by term
+    hint          suggest a move, with the line the engine expects after it
+    perft <n>     count the positions n plies ahead, after every move
+    skill <n>     make the engine play at level n, from 1 to 20 (its best)
+    undo          take back your last move
+    new           start a new game
+    resign        give up
+    flip          turn the board around
+    save [file]   save the game as PGN (default: chess-<time>.pgn in the
+                  --autosave directory or here)
+    pgn           print the game as PGN
+    help          print this
+    quit          leave
+";
+
+// Plays a game in the terminal: the board is printed after every move and
+// moves are typed at the prompt. `colors` turns on ANSI colours, otherwise
+// the board is plain Unicode.
+pub fn run<R: BufRead, W: Write>(
+    config: &Config,
+    input: R,
+    mut output: W,
+    colors: bool,
+) -> Result<(), Error> {
+    let start = match &config.fen {
+        Some(fen) => notation::position_from_fen(fen)?,
+        None => Chess::default(),
+    };
+
+    let (white, black) = config.players();
+    let mut waiting = |address| {
+        let _ = writeln!(output, "waiting for the other player on {}...", address);
+        let _ = output.flush();
+    };
+    let params = config.load_eval_params()?;
+    let white = white.create(config, &start, Color::White, &mut waiting)?;
+    let black = black.create(config, &start, Color::Black, &mut waiting)?;
+
+    let mut flipped = match (white.is_human(), black.is_human()) {
+        (false, true) => true,
+        (true, false) => false,
+        _ => config.human == Color::Black,
+    };
+
+    let mut controller = GameController::new(start, white, black);
+    if let Some((secs, increment)) = config.clock {
+        controller =
+            controller.with_clock(Duration::from_secs(secs), Duration::from_secs(increment));
+    }
+
+    let mut lines = input.lines();
+    // the board is only printed again when something changed
+    let mut redraw = true;
+    let io_error = |e: std::io::Error| Error::io("terminal", e);
+
+    writeln!(output, "Type `help` for the commands.").map_err(io_error)?;
+
+    loop {
+        // the other players move until it's a human's turn
+        let mut announced = false;
+        while controller.result().is_none() && !controller.is_human_turn() {
+            let turn = controller.position().turn();
+            let waiting = controller.waiting_for();
+            if !announced {
+                match waiting {
+                    Some(color) => writeln!(
+                        output,
+                        "waiting for {} to start...",
+                        controller.player(color).name()
+                    ),
+                    None => writeln!(output, "{} is thinking...", controller.player(turn).name()),
+                }
+                .map_err(io_error)?;
+                output.flush().map_err(io_error)?;
+                announced = true;
+            }
+
+            controller.update();
+
+            if controller.position().turn() == turn
+                && controller.waiting_for() == waiting
+                && controller.result().is_none()
+            {
+                // an engine in another process or someone over the network
+                thread::sleep(Duration::from_millis(10));
+            } else {
+                announced = false;
+            }
+
+            report(&mut controller, &mut output, config).map_err(io_error)?;
+        }
+
+        if redraw {
+            write!(output, "{}", render(&controller, flipped, colors)).map_err(io_error)?;
+        }
+
+        match controller.result() {
+            Some(_) => write!(output, "game over> ").map_err(io_error)?,
+            None => write!(
+                output,
+                "{} to move> ",
+                controller.position().turn().fold("White", "Black")
+            )
+            .map_err(io_error)?,
+        }
+        output.flush().map_err(io_error)?;
+
+        let line = match lines.next() {
+            Some(line) => line.map_err(io_error)?,
+            None => break,
+        };
+        let mut words = line.split_whitespace();
+        let moves_before = controller.moves().len();
+        redraw = false;
+
+        match (words.next(), words.next()) {
+            (None, _) => redraw = true,
+            (Some("quit"), _) | (Some("exit"), _) => break,
+            (Some("help"), _) | (Some("?"), _) => {
+                write!(output, "{}", COMMANDS).map_err(io_error)?
+            }
+            (Some("moves"), _) => {
+                let game = controller.position();
+                let moves: Vec<String> = game
+                    .legals()
+                    .iter()
+                    .map(|m| notation::san(game, m))
+                    .collect();
+                writeln!(output, "{}", moves.join(" ")).map_err(io_error)?;
+            }
+            (Some("eval"), _) => {
+                let explanation = eval::explain(controller.position().board(), &params);
+                write!(output, "{}", explanation).map_err(io_error)?
+            }
+            (Some("hint"), _) if controller.hint(&params) => {
+                // nothing else to do while waiting for it here
+                while controller.hint_pending() {
+                    controller.update();
+                }
+            }
+            (Some("hint"), _) => writeln!(output, "hints are for your turn").map_err(io_error)?,
+            (Some("perft"), depth) => match depth.and_then(|depth| depth.parse().ok()) {
+                Some(depth) if depth > 0 => {
+                    let counts = perft::divide(controller.position(), depth)?;
+                    for (m, count) in &counts {
+                        writeln!(output, "{}: {}", m, count).map_err(io_error)?;
+                    }
+                    let total: u64 = counts.iter().map(|(_, count)| count).sum();
+                    writeln!(output, "total: {}", total).map_err(io_error)?;
+                }
+                _ => writeln!(output, "usage: perft <depth>").map_err(io_error)?,
+            },
+            (Some("skill"), level) => match level.and_then(|level| level.parse().ok()) {
+                Some(level) if (skill::MIN_LEVEL..=skill::MAX_LEVEL).contains(&level) => {
+                    let skill = Skill::new(level);
+                    for &side in &[Color::White, Color::Black] {
+                        controller.player_mut(side).set_skill(skill.clone());
+                    }
+                    writeln!(output, "skill level {}, about {} Elo", level, skill.elo())
+                        .map_err(io_error)?;
+                }
+                _ => writeln!(output, "usage: skill <1-20>").map_err(io_error)?,
+            },
+            (Some("undo"), _) if !controller.can_take_back() => {
+                writeln!(output, "moves can't be taken back in this game").map_err(io_error)?
+            }
+            (Some("undo"), _) => controller.handle(Input::Undo),
+            (Some("new"), _) if !controller.can_take_back() => {
+                writeln!(output, "can't start a new game with this opponent").map_err(io_error)?
+            }
+            (Some("new"), _) => controller.handle(Input::NewGame),
+            (Some("resign"), _) => controller.handle(Input::Resign),
+            (Some("flip"), _) => {
+                flipped = !flipped;
+                redraw = true;
+            }
+            (Some("pgn"), _) => write!(output, "{}", controller.pgn()).map_err(io_error)?,
+            (Some("save"), file) => {
+                let saved = match file {
+                    Some(file) => std::fs::write(file, controller.pgn()).map(|_| file.into()),
+                    None => pgn::save(
+                        config.autosave.as_deref().unwrap_or_else(|| Path::new(".")),
+                        &controller.pgn(),
+                    ),
+                };
+                match saved {
+                    Ok(path) => writeln!(output, "saved to {}", path.display()),
+                    Err(err) => writeln!(output, "could not save the game: {}", err),
+                }
+                .map_err(io_error)?;
+            }
+            (Some(_), _) if controller.result().is_some() => {
+                writeln!(output, "the game is over, `new` starts another one").map_err(io_error)?
+            }
+            (Some(text), _) => match notation::parse_move(controller.position(), text) {
+                Ok(m) => controller.handle(Input::Play(m)),
+                Err(err) => {
+                    writeln!(output, "{}", err).map_err(io_error)?;
+
+                    let completions = complete(controller.position(), text);
+                    if !completions.is_empty() {
+                        writeln!(output, "did you mean: {}", completions.join(" "))
+                            .map_err(io_error)?;
+                    }
+                }
+            },
+        }
+
+        report(&mut controller, &mut output, config).map_err(io_error)?;
+        redraw |= controller.moves().len() != moves_before || !controller.is_human_turn();
+    }
+
+    Ok(())
+}
+
+// Prints what happened since last time.
+fn report<W: Write>(
+    controller: &mut GameController,
+    output: &mut W,
+    config: &Config,
+) -> std::io::Result<()> {
+    while let Some(event) = controller.poll_event() {
+        match event {
+            Event::Moved { san, by, .. } => {
+                let player = controller.player(by);
+                if player.is_human() {
+                    continue;
+                }
+
+                write!(output, "{} plays {}", player.name(), san)?;
+                match player.thinking() {
+                    Some(thinking) if thinking.book => write!(output, " (book)")?,
+                    Some(thinking) => {
+                        write!(
+                            output,
+                            " (depth {}, score {:+.2}, {} nodes",
+                            thinking.depth,
+                            f64::from(thinking.score) / 100.0,
+                            thinking.nodes
+                        )?;
+                        if thinking.tb_hits > 0 {
+                            write!(output, ", {} tablebase hits", thinking.tb_hits)?;
+                        }
+                        write!(output, ", {:.2}s)", thinking.time.as_secs_f64())?;
+                    }
+                    None => {}
+                }
+                writeln!(output)?;
+            }
+            Event::Illegal(_) => writeln!(output, "illegal move")?,
+            Event::PromotionNeeded { .. } => {}
+            Event::Undone => writeln!(output, "move taken back")?,
+            Event::NewGame => writeln!(output, "new game")?,
+            Event::Hint(hint) => writeln!(output, "hint: {}", hint)?,
+            Event::PlayerError(color, err) => {
+                writeln!(output, "{}: {}", color.fold("white", "black"), err)?
+            }
+            Event::GameOver(result) => {
+                writeln!(output, "{}", result)?;
+
+                if let Some(dir) = &config.autosave {
+                    match pgn::save(dir, &controller.pgn()) {
+                        Ok(path) => writeln!(output, "game saved to {}", path.display())?,
+                        Err(err) => writeln!(output, "could not save the game: {}", err)?,
+                    }
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// The legal moves starting with what was typed, in the notation it was typed in.
+fn complete(game: &Chess, text: &str) -> Vec<String> {
+    let legals = game.legals();
+
+    let sans = legals.iter().map(|m| notation::san(game, m));
+    let mut completions: Vec<String> = sans.filter(|san| san.starts_with(text)).collect();
+
+    if completions.is_empty() {
+        let text = text.to_ascii_lowercase();
+        completions = legals
+            .iter()
+            .map(|m| notation::uci(game, m))
+            .filter(|uci| uci.starts_with(&text))
+            .collect();
+    }
+
+    completions
+}
+
+// The board, the moves so far and the clocks.
+fn render(controller: &GameController, flipped: bool, colors: bool) -> String {
+    let game = controller.position();
+    let last_move = controller.last_move();
+    let check = if game.is_check() {
+        game.board().king_of(game.turn())
+    } else {
+        None
+    };
+
+    let mut text = String::new();
+    text.push('\n');
+
+    for row in 0..8 {
+        let rank = Rank::new(if flipped { row } else { 7 - row });
+        text.push_str(&format!(" {} ", rank.char()));
+
+        for col in 0..8 {
+            let file = File::new(if flipped { 7 - col } else { col });
+            let square = Square::from_coords(file, rank);
+            let highlighted =
+                last_move.is_some_and(|m| m.to() == square || m.from() == Some(square));
+
+            text.push_str(&render_square(
+                game.board(),
+                square,
+                highlighted,
+                check == Some(square),
+                colors,
+            ));
+        }
+        text.push('\n');
+    }
+
+    text.push_str("   ");
+    for col in 0..8 {
+        let file = File::new(if flipped { 7 - col } else { col });
+        text.push_str(&format!(" {} ", file.char()));
+    }
+    text.push('\n');
+
+    let moves = controller.moves();
+    if !moves.is_empty() {
+        text.push('\n');
+        text.push_str(&move_list(controller.start_position(), &moves));
+        text.push('\n');
+    }
+
+    if let (Some(white), Some(black)) = (
+        controller.remaining_time(Color::White),
+        controller.remaining_time(Color::Black),
+    ) {
+        text.push_str(&format!(
+            "white {}  black {}\n",
+            controller::format_clock(white),
+            controller::format_clock(black)
+        ));
+    }
+
+    text.push('\n');
+    text
+}
+
+fn render_square(
+    board: &Board,
+    square: Square,
+    highlighted: bool,
+    check: bool,
+    colors: bool,
+) -> String {
+    let piece = board.piece_at(square);
+
+    if !colors {
+        return match piece {
+            Some(piece) => format!(" {} ", glyph(piece, false)),
+            None => " · ".to_owned(),
+        };
+    }
+
+    let light = (u32::from(square.file()) + u32::from(square.rank())) % 2 == 1;
+    let (r, g, b) = match (check, highlighted, light) {
+        (true, _, _) => (220, 60, 60),
+        (false, true, true) => (205, 210, 106),
+        (false, true, false) => (170, 162, 58),
+        (false, false, true) => (240, 217, 181),
+        (false, false, false) => (181, 136, 99),
+    };
+
+    match piece {
+        Some(piece) => {
+            // the filled glyphs for both sides, coloured white or black
+            let fg = piece.color.fold("97", "30");
+            format!(
+                "\x1b[48;2;{};{};{}m\x1b[1;{}m {} \x1b[0m",
+                r,
+                g,
+                b,
+                fg,
+                glyph(piece, true)
+            )
+        }
+        None => format!("\x1b[48;2;{};{};{}m   \x1b[0m", r, g, b),
+    }
+}
+
+// ♔ for a white king, ♚ for a black one; `filled` uses the black ones for both
+fn glyph(piece: Piece, filled: bool) -> char {
+    let white = piece.color == Color::White && !filled;
+
+    match (piece.role, white) {
+        (Role::King, true) => '♔',
+        (Role::Queen, true) => '♕',
+        (Role::Rook, true) => '♖',
+        (Role::Bishop, true) => '♗',
+        (Role::Knight, true) => '♘',
+        (Role::Pawn, true) => '♙',
+        (Role::King, false) => '♚',
+        (Role::Queen, false) => '♛',
+        (Role::Rook, false) => '♜',
+        (Role::Bishop, false) => '♝',
+        (Role::Knight, false) => '♞',
+        (Role::Pawn, false) => '♟',
+    }
+}
+
+// "1. e4 e5 2. Nf3", numbered from the starting position
+fn move_list(start: &Chess, moves: &[&str]) -> String {
+    let mut text = String::new();
+    let mut number = start.fullmoves();
+    let black_starts = start.turn() == Color::Black;
+
+    for (i, san) in moves.iter().enumerate() {
+        let white_to_move = (i % 2 == 0) != black_starts;
+
+        if white_to_move {
+            text.push_str(&format!("{}. ", number));
+        } else if i == 0 {
+            text.push_str(&format!("{}... ", number));
+        }
+
+        text.push_str(san);
+        text.push(' ');
+
+        if !white_to_move {
+            number += 1;
+        }
+    }
+
+    text.trim_end().to_owned()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::player::PlayerSpec;
+
+    #[test]
+    fn test_terminal_game() {
+        let config = Config {
+            white: Some(PlayerSpec::Human),
+            black: Some(PlayerSpec::Human),
+            ..Config::default()
+        };
+
+        let input = "e4\nNf\nNf6\nundo\nmoves\ne5\nquit\n";
+        let mut output = Vec::new();
+        run(&config, input.as_bytes(), &mut output, false).unwrap();
+        let output = String::from_utf8(output).unwrap();
+
+        assert!(output.contains("did you mean: Nf6"));
+        assert!(output.contains("move taken back"));
+        assert!(output.contains("1. e4 e5"));
+        // black's pieces at the top, the last board after 1. e4 e5
+        assert!(output.contains(" 8  ♜  ♞  ♝  ♛  ♚  ♝  ♞  ♜ \n"));
+        assert!(output.contains(" 4  ·  ·  ·  ·  ♙  ·  ·  · \n"));
+    }
+}
@@ -0,0 +1,268 @@
+extern crate shakmaty;
+
+use shakmaty::{Chess, Position, Setup};
+
+use std::fs;
+use std::path::Path;
+
+use crate::error::Error;
+use crate::eval;
+use crate::notation;
+use crate::params::EvalParams;
+
+// Texel's tuning method
+// (https://www.chessprogramming.org/Texel%27s_Tuning_Method): the
+// evaluation of a lot of positions from real games, turned into an
+// expected score by a sigmoid, should be as close as possible to how those
+// games ended. The parameters are nudged one at a time for as long as that
+// makes the squared error smaller.
+
+// A position and the result of its game for white: 1, 0.5 or 0.
+#[derive(Clone, Debug)]
+pub struct Sample {
+    pub game: Chess,
+    pub result: f64,
+}
+
+// One line of an EPD or FEN file with the result somewhere after the
+// position, the ways the usual tuning sets write it:
+//
+//     <fen> c9 "1-0";
+//     <fen> [0.5]
+//     <fen> [1/2-1/2]
+//
+// None for blank lines and '#' comments.
+pub fn parse_sample(line: &str) -> Result<Option<Sample>, Error> {
+    let line = line.trim();
+    if line.is_empty() || line.starts_with('#') {
+        return Ok(None);
+    }
+
+    // EPD has the first four fields of a FEN, a FEN the move counters too
+    let words: Vec<&str> = line.split_whitespace().collect();
+    let mut fields = words.len().min(4);
+    while fields < words.len().min(6) && words[fields].parse::<u32>().is_ok() {
+        fields += 1;
+    }
+    let game = notation::position_from_fen(&words[..fields].join(" "))?;
+
+    let result = words[fields..].iter().find_map(|word| {
+        let bracketed = word.starts_with('[');
+        match word.trim_matches(|c| c == '"' || c == ';' || c == '[' || c == ']') {
+            "1-0" => Some(1.0),
+            "0-1" => Some(0.0),
+            "1/2-1/2" => Some(0.5),
+            // a plain number could be any EPD operand
+            number if bracketed => number.parse().ok().filter(|r| (0.0..=1.0).contains(r)),
+            _ => None,
+        }
+    });
+
+    match result {
+        Some(result) => Ok(Some(Sample { game, result })),
+        None => Err(Error::Config(format!("no game result in: {}", line))),
+    }
+}
+
+// Every position in `path`. With `quiescence` each one is replaced by
+// where the captures from it end (as the built-in parameters see it), so
+// the tuner doesn't learn from positions in the middle of an exchange.
+pub fn load_samples(path: &Path, quiescence: bool) -> Result<Vec<Sample>, Error> {
+    let text = fs::read_to_string(path).map_err(|e| Error::io(path.display(), e))?;
+    let mut samples = Vec::new();
+    let params = EvalParams::default();
+
+    for (i, line) in text.lines().enumerate() {
+        let in_file = |e: Error| Error::Config(format!("{}:{}: {}", path.display(), i + 1, e));
+        if let Some(mut sample) = parse_sample(line).map_err(in_file)? {
+            if quiescence {
+                sample.game = quiet(sample.game, &params);
+            }
+            samples.push(sample);
+        }
+    }
+
+    Ok(samples)
+}
+
+// the position at the end of the best line of captures from `game`
+fn quiet(game: Chess, params: &EvalParams) -> Chess {
+    let mut nodes = 0;
+    quiescence(game, -100_000, 100_000, &mut nodes, params).1
+}
+
+// From the side to move's point of view, in centipawns. Standing pat is
+// always allowed, so only the captures that win something get played.
+fn quiescence(
+    game: Chess,
+    mut alpha: i32,
+    beta: i32,
+    nodes: &mut u64,
+    params: &EvalParams,
+) -> (i32, Chess) {
+    *nodes += 1;
+    let stand_pat = game.turn().fold(1, -1) * eval::explain(game.board(), params).score();
+    if stand_pat >= beta || *nodes > 100_000 {
+        return (stand_pat, game);
+    }
+    alpha = alpha.max(stand_pat);
+
+    let mut best = (stand_pat, None);
+    for m in game.legals().iter().filter(|m| m.is_capture()) {
+        let mut next = game.clone();
+        next.play_unchecked(m);
+        let (score, leaf) = quiescence(next, -beta, -alpha, nodes, params);
+        let score = -score;
+
+        if score > best.0 {
+            best = (score, Some(leaf));
+        }
+        alpha = alpha.max(score);
+        if alpha >= beta {
+            break;
+        }
+    }
+
+    (best.0, best.1.unwrap_or(game))
+}
+
+// the expected result for white of a position white is `score` centipawns up in
+fn sigmoid(score: f64, k: f64) -> f64 {
+    1.0 / (1.0 + 10f64.powf(-k * score / 400.0))
+}
+
+// the mean squared difference between the results and what `params` expect
+pub fn error(samples: &[Sample], params: &EvalParams, k: f64) -> f64 {
+    let total: f64 = samples
+        .iter()
+        .map(|sample| {
+            let score = eval::explain(sample.game.board(), params).score() as f64;
+            (sample.result - sigmoid(score, k)).powi(2)
+        })
+        .sum();
+    total / samples.len().max(1) as f64
+}
+
+// The sigmoid's scaling that fits `params` best as they are, found before
+// tuning so that the tuner changes the evaluation and not its scale.
+pub fn best_k(samples: &[Sample], params: &EvalParams) -> f64 {
+    let mut best = (1.0, error(samples, params, 1.0));
+    let mut step = 0.5;
+
+    // closer and closer around the best so far
+    while step > 0.001 {
+        for k in &[best.0 - step, best.0 + step] {
+            if *k > 0.0 {
+                let e = error(samples, params, *k);
+                if e < best.1 {
+                    best = (*k, e);
+                }
+            }
+        }
+        step /= 2.0;
+    }
+
+    best.0
+}
+
+// How a pass of `tune` went.
+#[derive(Clone, Debug)]
+pub struct Pass {
+    pub number: u32,
+    pub error: f64,
+    // the parameters that were moved
+    pub changed: usize,
+}
+
+// Local search: every parameter is tried one higher and one lower and kept
+// where the error is smallest, for up to `passes` passes or until a pass
+// changes nothing. `progress` sees the parameters after every pass.
+pub fn tune<F: FnMut(&Pass, &EvalParams)>(
+    samples: &[Sample],
+    params: &EvalParams,
+    k: f64,
+    passes: u32,
+    mut progress: F,
+) -> EvalParams {
+    let mut params = params.clone();
+    let mut values = params.to_vec();
+    let mut best_error = error(samples, &params, k);
+
+    // the king's value and the pawns on the first and last ranks change nothing
+    let tunable: Vec<usize> = params
+        .names()
+        .iter()
+        .enumerate()
+        .filter(|(_, (name, i))| match name.as_str() {
+            "values" => *i != 5,
+            "pawn_mg" | "pawn_eg" => (8..56).contains(i),
+            _ => true,
+        })
+        .map(|(index, _)| index)
+        .collect();
+
+    for number in 1..=passes {
+        let mut changed = 0;
+
+        for &i in &tunable {
+            for delta in &[1, -1] {
+                values[i] += delta;
+                params.set_vec(&values);
+                let e = error(samples, &params, k);
+
+                if e < best_error {
+                    best_error = e;
+                    changed += 1;
+                    break;
+                }
+                values[i] -= delta;
+            }
+        }
+
+        params.set_vec(&values);
+        let pass = Pass {
+            number,
+            error: best_error,
+            changed,
+        };
+        progress(&pass, &params);
+
+        if changed == 0 {
+            break;
+        }
+    }
+
+    params
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_tune() {
+        let lines = [
+            "4k3/8/8/8/8/8/PPP5/4K3 w - - c9 \"1-0\";",
+            "4k3/ppp5/8/8/8/8/8/4K3 b - - 0 1 [0.0]",
+            "4k3/pp6/8/8/8/8/PP6/4K3 w - - [1/2-1/2]",
+            "4k3/8/8/3q4/8/8/3R4/3QK3 w - - [0.5]",
+        ];
+        let samples: Vec<Sample> = lines
+            .iter()
+            .map(|line| parse_sample(line).unwrap().unwrap())
+            .collect();
+        assert_eq!(samples[0].result, 1.0);
+        assert_eq!(samples[2].result, 0.5);
+        assert!(parse_sample("# a comment").unwrap().is_none());
+        assert!(parse_sample("4k3/8/8/8/8/8/8/4K3 w - - hmvc 0;").is_err());
+
+        // white takes the queen first
+        let game = notation::position_from_fen("4k3/8/8/3q4/8/8/3R4/3QK3 w - - 0 1").unwrap();
+        let params = EvalParams::default();
+        assert_eq!(quiet(game, &params).board().queens().count(), 1);
+
+        let k = best_k(&samples, &params);
+        let tuned = tune(&samples, &params, k, 1, |_, _| {});
+        assert!(error(&samples, &tuned, k) < error(&samples, &params, k));
+    }
+}
@@ -0,0 +1,322 @@
+extern crate shakmaty;
+
+use shakmaty::{Chess, Position, Setup};
+
+use std::io::{self, BufRead, Write};
+use std::path::Path;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use crate::ai;
+use crate::bench;
+use crate::error::Error;
+use crate::eval;
+use crate::notation;
+use crate::params::EvalParams;
+use crate::perft;
+use crate::skill::{self, Skill};
+use crate::syzygy::Tablebase;
+
+pub const ENGINE_NAME: &str = "chess";
+pub const ENGINE_AUTHOR: &str = "Alice Micheloni";
+
+// Used when `go` doesn't say how deep to search.
+const DEFAULT_DEPTH: u32 = 3;
+// With a clock the time decides, this is just a safety net.
+const MAX_DEPTH: u32 = 64;
+
+// Speaks the Universal Chess Interface
+// (http://wbec-ridderkerk.nl/html/UCIProtocol.html) over `input` and
+// `output` until `quit` or the end of the input. Searching is synchronous,
+// so `stop` has nothing to stop.
+pub fn run<R: BufRead, W: Write>(input: R, mut output: W) -> io::Result<()> {
+    let mut game = Chess::default();
+    let mut tablebase = None;
+    let mut params = Rc::new(EvalParams::default());
+    // Skill Level, unless UCI_LimitStrength asks for UCI_Elo instead
+    let mut skill = Skill::new(skill::MAX_LEVEL);
+    let (mut skill_level, mut limit_strength, mut elo) = (skill::MAX_LEVEL, false, skill::MAX_ELO);
+
+    for line in input.lines() {
+        let line = line?;
+        let mut words = line.split_whitespace();
+
+        match words.next() {
+            Some("uci") => {
+                writeln!(output, "id name {}", ENGINE_NAME)?;
+                writeln!(output, "id author {}", ENGINE_AUTHOR)?;
+                writeln!(output, "option name SyzygyPath type string default <empty>")?;
+                writeln!(output, "option name EvalParams type string default <empty>")?;
+                writeln!(
+                    output,
+                    "option name Skill Level type spin default {0} min {1} max {0}",
+                    skill::MAX_LEVEL,
+                    skill::MIN_LEVEL
+                )?;
+                writeln!(
+                    output,
+                    "option name UCI_LimitStrength type check default false"
+                )?;
+                writeln!(
+                    output,
+                    "option name UCI_Elo type spin default {0} min {1} max {0}",
+                    skill::MAX_ELO,
+                    skill::MIN_ELO
+                )?;
+                writeln!(output, "uciok")?;
+            }
+            Some("isready") => writeln!(output, "readyok")?,
+            Some("ucinewgame") => game = Chess::default(),
+            Some("position") => match parse_position(words.collect()) {
+                Ok(position) => game = position,
+                Err(err) => writeln!(output, "info string {}", err)?,
+            },
+            Some("setoption") => match parse_option(words.collect()) {
+                Some((name, value)) if name.eq_ignore_ascii_case("SyzygyPath") => {
+                    tablebase = None;
+                    if !value.is_empty() && value != "<empty>" {
+                        match Tablebase::open(Path::new(&value)) {
+                            Ok(opened) => tablebase = Some(Rc::new(opened)),
+                            Err(err) => writeln!(output, "info string {}", err)?,
+                        }
+                    }
+                }
+                Some((name, value)) if name.eq_ignore_ascii_case("EvalParams") => {
+                    if value.is_empty() || value == "<empty>" {
+                        params = Rc::new(EvalParams::default());
+                    } else {
+                        match EvalParams::load(Path::new(&value)) {
+                            Ok(loaded) => params = Rc::new(loaded),
+                            Err(err) => writeln!(output, "info string {}", err)?,
+                        }
+                    }
+                }
+                Some((name, value)) if name.eq_ignore_ascii_case("Skill Level") => {
+                    match value.parse() {
+                        Ok(level) => skill_level = Skill::new(level).level,
+                        Err(_) => writeln!(output, "info string invalid skill level {}", value)?,
+                    }
+                }
+                Some((name, value)) if name.eq_ignore_ascii_case("UCI_LimitStrength") => {
+                    limit_strength = value.eq_ignore_ascii_case("true")
+                }
+                Some((name, value)) if name.eq_ignore_ascii_case("UCI_Elo") => {
+                    match value.parse() {
+                        Ok(rating) => elo = rating,
+                        Err(_) => writeln!(output, "info string invalid Elo {}", value)?,
+                    }
+                }
+                Some((name, _)) => writeln!(output, "info string unknown option {}", name)?,
+                None => writeln!(
+                    output,
+                    "info string expected setoption name <id> [value <x>]"
+                )?,
+            },
+            Some("go") if line.split_whitespace().nth(1) == Some("perft") => {
+                perft(&game, words.nth(1), &mut output)?
+            }
+            Some("go") => {
+                skill.level = if limit_strength {
+                    Skill::from_elo(elo).level
+                } else {
+                    skill_level
+                };
+                go(
+                    &game,
+                    words.collect(),
+                    tablebase.as_ref(),
+                    &params,
+                    &mut skill,
+                    &mut output,
+                )?
+            }
+            // not part of UCI, but handy when talking to the engine by hand
+            Some("d") => writeln!(output, "{}", notation::to_fen(&game))?,
+            Some("eval") => write!(output, "{}", eval::explain(game.board(), &params))?,
+            // the parameters in use, as an EvalParams file
+            Some("params") => write!(output, "{}", params)?,
+            Some("bench") => run_bench(words.next(), &mut output)?,
+            Some("quit") => break,
+            Some("stop") | Some("ponderhit") | None => {}
+            Some(command) => writeln!(output, "info string unknown command {}", command)?,
+        }
+
+        output.flush()?;
+    }
+
+    Ok(())
+}
+
+// position [startpos | fen <fen>] [moves <move> ...]
+fn parse_position(words: Vec<&str>) -> Result<Chess, Error> {
+    let moves_at = words.iter().position(|w| *w == "moves");
+    let (setup, moves) = match moves_at {
+        Some(i) => (&words[..i], &words[i + 1..]),
+        None => (&words[..], &[][..]),
+    };
+
+    let mut game = match setup.split_first() {
+        Some((&"startpos", _)) => Chess::default(),
+        Some((&"fen", fen)) => notation::position_from_fen(&fen.join(" "))?,
+        _ => return Err(Error::engine("position", "expected startpos or fen")),
+    };
+
+    for text in moves {
+        let m = notation::parse_move(&game, text)?;
+        game = game
+            .play(&m)
+            .map_err(|_| Error::Move(format!("illegal move: {}", text)))?;
+    }
+
+    Ok(game)
+}
+
+// setoption name <id> [value <x>], both can have spaces
+fn parse_option(words: Vec<&str>) -> Option<(String, String)> {
+    let (first, rest) = words.split_first()?;
+    if *first != "name" {
+        return None;
+    }
+    let value_at = rest.iter().position(|w| *w == "value");
+    let (name, value) = match value_at {
+        Some(i) => (&rest[..i], &rest[i + 1..]),
+        None => (rest, &[][..]),
+    };
+    if name.is_empty() {
+        return None;
+    }
+    Some((name.join(" "), value.join(" ")))
+}
+
+// go perft <depth>, like stockfish: the count after every move and the total
+fn perft<W: Write>(game: &Chess, depth: Option<&str>, output: &mut W) -> io::Result<()> {
+    let depth = match depth.and_then(|depth| depth.parse().ok()) {
+        Some(depth) if depth > 0 => depth,
+        _ => return writeln!(output, "info string expected go perft <depth>"),
+    };
+
+    match perft::divide(game, depth) {
+        Ok(counts) => {
+            for (m, count) in &counts {
+                writeln!(output, "{}: {}", m, count)?;
+            }
+            let total: u64 = counts.iter().map(|(_, count)| count).sum();
+            writeln!(output, "\nNodes searched: {}", total)
+        }
+        Err(err) => writeln!(output, "info string {}", err),
+    }
+}
+
+// bench [depth], the same searches every time to compare builds and machines
+fn run_bench<W: Write>(depth: Option<&str>, output: &mut W) -> io::Result<()> {
+    let depth = match depth.map(|depth| depth.parse()) {
+        None => bench::DEFAULT_DEPTH,
+        Some(Ok(depth)) if depth > 0 => depth,
+        _ => return writeln!(output, "info string expected bench [depth]"),
+    };
+
+    let result = bench::bench(depth);
+    writeln!(output, "Positions: {}", result.positions)?;
+    writeln!(output, "Depth: {}", depth)?;
+    writeln!(output, "Total time (ms): {}", result.time.as_millis())?;
+    writeln!(output, "Nodes searched: {}", result.nodes)?;
+    writeln!(output, "Nodes/second: {}", result.nps())
+}
+
+// go [depth <plies>] [movetime <ms>] [wtime <ms>] [btime <ms>] [winc <ms>] [binc <ms>] [movestogo <n>]
+fn go<W: Write>(
+    game: &Chess,
+    words: Vec<&str>,
+    tablebase: Option<&Rc<Tablebase>>,
+    params: &Rc<EvalParams>,
+    skill: &mut Skill,
+    output: &mut W,
+) -> io::Result<()> {
+    let value = |name: &str| -> Option<u64> {
+        words
+            .iter()
+            .position(|w| *w == name)
+            .and_then(|i| words.get(i + 1))
+            .and_then(|v| v.parse().ok())
+    };
+
+    let (time, inc) = game
+        .turn()
+        .fold((value("wtime"), value("winc")), (value("btime"), value("binc")));
+
+    // use a slice of the clock, assuming there are still `movestogo` moves left
+    let clock = time.map(|time| {
+        let moves_to_go = value("movestogo").unwrap_or(30).max(1);
+        Duration::from_millis(time / moves_to_go + inc.unwrap_or(0) / 2)
+    });
+
+    let movetime = value("movetime").map(Duration::from_millis).or(clock);
+    let depth = match value("depth") {
+        Some(depth) => (depth as u32).max(1),
+        None if movetime.is_some() => MAX_DEPTH,
+        None => DEFAULT_DEPTH,
+    };
+
+    let start = Instant::now();
+    let result = ai::search_limited(
+        game,
+        skill.depth(depth),
+        movetime,
+        skill.max_nodes(),
+        tablebase,
+        params,
+    );
+    if let Some(err) = tablebase.and_then(|tablebase| tablebase.take_error()) {
+        writeln!(output, "info string {}", err)?;
+    }
+    let result = match result {
+        Some(result) => result,
+        None => {
+            writeln!(output, "bestmove 0000")?;
+            return Ok(());
+        }
+    };
+    let (m, score) = skill.pick(&result);
+    let best = notation::uci(game, &m);
+
+    // our pawns are worth 10, UCI counts in hundredths of a pawn
+    writeln!(
+        output,
+        "info depth {} score cp {} nodes {} tbhits {} time {} pv {}",
+        result.depth,
+        score * 10,
+        result.nodes,
+        result.tb_hits,
+        start.elapsed().as_millis(),
+        best
+    )?;
+    writeln!(output, "bestmove {}", best)?;
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use shakmaty::Color;
+
+    #[test]
+    fn test_uci_session() {
+        let input = "uci\nisready\nposition startpos moves e2e4 e7e5\ngo depth 2\nquit\ngo depth 1\n";
+        let mut output = Vec::new();
+        run(input.as_bytes(), &mut output).unwrap();
+
+        let output = String::from_utf8(output).unwrap();
+        let lines: Vec<&str> = output.lines().collect();
+        assert!(lines.contains(&"uciok"));
+        assert!(lines.contains(&"readyok"));
+
+        // exactly one search, the one after quit never ran
+        let bestmoves: Vec<_> = lines.iter().filter(|l| l.starts_with("bestmove ")).collect();
+        assert_eq!(bestmoves.len(), 1);
+
+        let game = parse_position(vec!["startpos", "moves", "e2e4", "e7e5"]).unwrap();
+        assert_eq!(game.turn(), Color::White);
+        assert!(notation::parse_move(&game, &bestmoves[0]["bestmove ".len()..]).is_ok());
+    }
+}
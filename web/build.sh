@@ -0,0 +1,24 @@
+#! /bin/sh
+# Builds the game for the browser into web/, next to index.html.
+# Needs emscripten (emcc on the PATH, e.g. after `source emsdk_env.sh`) and
+# `rustup target add wasm32-unknown-emscripten`.
+
+set -e
+cd "$(dirname "$0")/.."
+
+TARGET=wasm32-unknown-emscripten
+
+# SDL2 and SDL2_image come from emscripten's ports; the sprites are embedded
+# in the binary by the portable feature, so there's nothing to preload.
+# The chess_* functions are what the buttons of the page call.
+export EMCC_CFLAGS="-s USE_SDL=2 -s USE_SDL_IMAGE=2 -s SDL2_IMAGE_FORMATS=[\"png\"] \
+-s ALLOW_MEMORY_GROWTH=1 \
+-s EXPORTED_FUNCTIONS=[\"_main\",\"_chess_new_game\",\"_chess_undo\",\"_chess_pgn\"] \
+-s EXPORTED_RUNTIME_METHODS=[\"ccall\",\"UTF8ToString\"]"
+
+cargo build --release --target $TARGET --bin chess --features portable
+
+cp target/$TARGET/release/chess.js target/$TARGET/release/chess.wasm web/
+
+echo "built web/chess.js and web/chess.wasm, serve web/ with any web server:"
+echo "    python3 -m http.server --directory web"